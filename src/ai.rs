@@ -0,0 +1,157 @@
+use ::rand::Rng;
+use ::rand::thread_rng;
+
+use crate::bitboard::{BitBoard, Collapser};
+use crate::lines::LineTable;
+use crate::renju;
+use crate::{Correlations, DoubleWinRule, GameConfig, Player, ProbPiece, WinCondition};
+
+// 评估预览时的随机坍缩次数
+const PREVIEW_SAMPLES: usize = 200;
+// 进一步模拟获胜概率的候选点数与每点坍缩次数
+const LOOKAHEAD_CANDIDATES: usize = 6;
+const LOOKAHEAD_SAMPLES: usize = 100;
+// 模拟获胜概率在评估值中的权重
+const WIN_WEIGHT: f32 = 2000.0;
+
+// 附近（两路以内）是否已有棋子
+fn has_neighbor(board: &[Vec<ProbPiece>], row: usize, col: usize) -> bool {
+    let size = board.len() as i32;
+    for dr in -2..=2 {
+        for dc in -2..=2 {
+            let r = row as i32 + dr;
+            let c = col as i32 + dc;
+            if r >= 0 && r < size && c >= 0 && c < size && board[r as usize][c as usize] != ProbPiece::Empty {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+// 评估空位：由维护的连线窗口统计表读取经过该点的所有窗口中双方棋子的期望数量
+fn evaluate(lines: &LineTable, row: usize, col: usize, player: Player) -> f32 {
+    let length = lines.length() as f32;
+    let mut attack = 0.0;
+    let mut defense = 0.0;
+    for window in lines.windows_through(row, col) {
+        let own = window.expected(player);
+        let opp = window.expected(player.opponent());
+        attack += 6f32.powf(own) * (1.0 - opp / length);
+        defense += 6f32.powf(opp) * (1.0 - own / length);
+    }
+    attack + defense * 0.9
+}
+
+// 在该点落下 piece 后坍缩，经过该点形成己方获胜连线的概率（只检查经过该点的连线）
+fn win_chance(
+    collapser: &Collapser,
+    size: usize,
+    (row, col): (usize, usize),
+    piece: ProbPiece,
+    player: Player,
+    win: WinCondition,
+) -> f32 {
+    let mut rng = thread_rng();
+    let mut collapsed = BitBoard::new(size);
+    let mut wins = 0;
+    for _ in 0..LOOKAHEAD_SAMPLES {
+        collapser.collapse_into(&mut rng, &mut collapsed);
+        let color = if rng.gen_range(0..100) < piece.black_percent() {
+            Player::Black
+        } else {
+            Player::White
+        };
+        collapsed.place(row, col, color);
+        if collapsed.win_through(row, col, win) == Some(player) {
+            wins += 1;
+        }
+    }
+    wins as f32 / LOOKAHEAD_SAMPLES as f32
+}
+
+// 电脑选择落子位置：在已有棋子附近的空位中按评估值取前几名，再模拟坍缩比较获胜概率
+pub fn choose_move(
+    board: &[Vec<ProbPiece>],
+    correlations: &Correlations,
+    lines: &LineTable,
+    piece: ProbPiece,
+    player: Player,
+    config: &GameConfig,
+) -> Option<(usize, usize)> {
+    let win = config.win_condition();
+    let renju = config.renju_active() && player == Player::Black;
+    let size = board.len();
+    if board.iter().all(|line| line.iter().all(|&piece| piece == ProbPiece::Empty)) {
+        return Some((size / 2, size / 2));
+    }
+
+    let mut rng = thread_rng();
+    let mut candidates = Vec::new();
+    for row in 0..size {
+        for col in 0..size {
+            if board[row][col] != ProbPiece::Empty || !has_neighbor(board, row, col) {
+                continue;
+            }
+            if renju && renju::is_forbidden(board, row, col) {
+                continue;
+            }
+            let score = evaluate(lines, row, col, player) + rng.gen_range(0.0..0.01);
+            candidates.push((score, row, col));
+        }
+    }
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+    candidates.truncate(LOOKAHEAD_CANDIDATES);
+
+    let collapser = Collapser::new(board, correlations);
+    candidates
+        .into_iter()
+        .map(|(score, row, col)| {
+            let chance = win_chance(&collapser, size, (row, col), piece, player, win);
+            (score + chance * WIN_WEIGHT, row, col)
+        })
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, row, col)| (row, col))
+}
+
+// 交换开局中电脑选择颜色：黑棋期望数领先超过一枚时执黑，否则执即将行棋的白方
+pub fn choose_colour(board: &[Vec<ProbPiece>]) -> Player {
+    let (black, white) = board
+        .iter()
+        .flatten()
+        .filter(|&&piece| piece != ProbPiece::Empty)
+        .fold((0.0, 0.0), |(black, white), piece| {
+            let (black_odds, white_odds) = piece.odds();
+            (black + black_odds as f32 / 100.0, white + white_odds as f32 / 100.0)
+        });
+    if black - white > 1.0 { Player::Black } else { Player::White }
+}
+
+// 电脑是否预览：随机坍缩多次，己方获胜的概率明显高于对方时才预览
+// 双方同时成五时：预览方获胜的规则下算己方获胜，重新坍缩时不计，其余算作对己方不利
+pub fn should_preview(
+    board: &[Vec<ProbPiece>],
+    correlations: &Correlations,
+    player: Player,
+    config: &GameConfig,
+) -> bool {
+    let win = config.win_condition();
+    let collapser = Collapser::new(board, correlations);
+    let mut collapsed = BitBoard::new(board.len());
+    let mut rng = thread_rng();
+    let mut own_wins = 0;
+    let mut opp_wins = 0;
+    for _ in 0..PREVIEW_SAMPLES {
+        collapser.collapse_into(&mut rng, &mut collapsed);
+        let own = collapsed.has_win(player, win);
+        let opp = collapsed.has_win(player.opponent(), win);
+        match (own, opp) {
+            (true, true) if config.double_win == DoubleWinRule::ObserverWins => own_wins += 1,
+            (true, true) if config.double_win == DoubleWinRule::Reroll => {}
+            (true, false) => own_wins += 1,
+            (_, true) => opp_wins += 1,
+            _ => {}
+        }
+    }
+    own_wins * 3 >= PREVIEW_SAMPLES && own_wins > opp_wins * 3
+}
@@ -0,0 +1,115 @@
+// 预览坍缩动画的各阶段（以正常速度计，单位秒）
+const FLICKER_TIME: f32 = 0.4; // 所有棋子翻转闪烁
+const SETTLE_SPREAD: f32 = 0.6; // 棋子依次落定的时间跨度
+const SETTLE_DURATION: f32 = 0.15; // 单枚棋子由翻转到落定的时间
+const SWEEP_DURATION: f32 = 0.5; // 获胜连线依次亮起
+const BANNER_DURATION: f32 = 0.4; // 获胜横幅滑入
+
+// 缓动函数：先快后慢
+pub fn ease_out_cubic(t: f32) -> f32 {
+    1.0 - (1.0 - t).powi(3)
+}
+
+// 缓动函数：两端慢中间快
+pub fn ease_in_out_sine(t: f32) -> f32 {
+    (1.0 - (t * std::f32::consts::PI).cos()) / 2.0
+}
+
+// 补间：在 [start, start + duration] 时间段内由0过渡到1
+#[derive(Clone, Copy, Debug)]
+pub struct Tween {
+    pub start: f32,
+    pub duration: f32,
+    pub easing: fn(f32) -> f32,
+}
+
+impl Tween {
+    pub fn new(start: f32, duration: f32, easing: fn(f32) -> f32) -> Self {
+        Tween { start, duration, easing }
+    }
+
+    pub fn end(&self) -> f32 {
+        self.start + self.duration
+    }
+
+    // 某一时刻的补间值（0到1）
+    pub fn value(&self, time: f32) -> f32 {
+        if self.duration <= 0.0 {
+            return if time >= self.start { 1.0 } else { 0.0 };
+        }
+        (self.easing)(((time - self.start) / self.duration).clamp(0.0, 1.0))
+    }
+}
+
+// 时间线：按速度倍率推进已播放时间，到达终点即结束
+#[derive(Clone, Copy, Debug)]
+pub struct Timeline {
+    pub elapsed: f32,
+    pub speed: f32,
+    pub end: f32,
+}
+
+impl Timeline {
+    pub fn new(speed: f32, end: f32) -> Self {
+        Timeline { elapsed: 0.0, speed, end }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.elapsed = (self.elapsed + dt * self.speed).min(self.end);
+    }
+
+    pub fn finished(&self) -> bool {
+        self.elapsed >= self.end
+    }
+}
+
+// 预览坍缩动画：棋子翻转闪烁后依次落定，随后获胜连线扫过、获胜横幅滑下
+#[derive(Clone, Copy, Debug)]
+pub struct CollapseAnimation {
+    pub timeline: Timeline,
+    pub sweep: Tween,
+    pub banner: Tween,
+}
+
+impl CollapseAnimation {
+    pub fn new(speed: f32) -> Self {
+        let settle_end = FLICKER_TIME + SETTLE_SPREAD + SETTLE_DURATION;
+        let sweep = Tween::new(settle_end, SWEEP_DURATION, ease_in_out_sine);
+        let banner = Tween::new(sweep.end(), BANNER_DURATION, ease_out_cubic);
+        CollapseAnimation {
+            timeline: Timeline::new(speed, banner.end()),
+            sweep,
+            banner,
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.timeline.update(dt);
+    }
+
+    pub fn finished(&self) -> bool {
+        self.timeline.finished()
+    }
+
+    // 当前已播放时间
+    pub fn time(&self) -> f32 {
+        self.timeline.elapsed
+    }
+
+    // 某枚棋子的落定进度：0为仍在翻转，1为已落定（落定顺序由位置决定，看起来随机）
+    pub fn settle(&self, row: usize, col: usize) -> f32 {
+        let hash = (row as u32).wrapping_mul(73_856_093) ^ (col as u32).wrapping_mul(19_349_663);
+        let order = (hash.wrapping_mul(2_654_435_761) >> 16) as f32 / 65_536.0;
+        Tween::new(FLICKER_TIME + order * SETTLE_SPREAD, SETTLE_DURATION, ease_out_cubic).value(self.time())
+    }
+
+    // 获胜连线扫过的进度
+    pub fn sweep(&self) -> f32 {
+        self.sweep.value(self.time())
+    }
+
+    // 获胜横幅滑入的进度
+    pub fn banner(&self) -> f32 {
+        self.banner.value(self.time())
+    }
+}
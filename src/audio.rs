@@ -0,0 +1,70 @@
+use macroquad::audio::{load_sound_from_bytes, play_sound, PlaySoundParams, Sound};
+
+use crate::settings::Settings;
+
+// 音效资源：编译时嵌入程序，无需额外文件
+const PLACE_WAV: &[u8] = include_bytes!("../assets/sounds/place.wav");
+const COLLAPSE_WAV: &[u8] = include_bytes!("../assets/sounds/collapse.wav");
+const WIN_WAV: &[u8] = include_bytes!("../assets/sounds/win.wav");
+const CLICK_WAV: &[u8] = include_bytes!("../assets/sounds/click.wav");
+
+// 音效种类
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SoundEffect {
+    Place,    // 落子
+    Collapse, // 预览棋盘坍缩
+    Win,      // 对局结束
+    Click,    // 按钮点击
+}
+
+// 已加载的音效：加载失败的音效为None，播放时忽略
+pub struct Sounds {
+    place: Option<Sound>,
+    collapse: Option<Sound>,
+    win: Option<Sound>,
+    click: Option<Sound>,
+}
+
+// 加载一个音效，失败时打印错误
+async fn load(name: &str, bytes: &[u8]) -> Option<Sound> {
+    match load_sound_from_bytes(bytes).await {
+        Ok(sound) => Some(sound),
+        Err(err) => {
+            eprintln!("Failed to load {} sound: {}", name, err);
+            None
+        }
+    }
+}
+
+impl Sounds {
+    pub async fn load() -> Sounds {
+        Sounds {
+            place: load("place", PLACE_WAV).await,
+            collapse: load("collapse", COLLAPSE_WAV).await,
+            win: load("win", WIN_WAV).await,
+            click: load("click", CLICK_WAV).await,
+        }
+    }
+
+    // 按设置中的音量播放音效，静音时不播放
+    pub fn play(&self, effect: SoundEffect, settings: &Settings) {
+        if !settings.sound || settings.volume == 0 {
+            return;
+        }
+        let sound = match effect {
+            SoundEffect::Place => &self.place,
+            SoundEffect::Collapse => &self.collapse,
+            SoundEffect::Win => &self.win,
+            SoundEffect::Click => &self.click,
+        };
+        if let Some(sound) = sound {
+            play_sound(
+                sound,
+                PlaySoundParams {
+                    looped: false,
+                    volume: settings.volume as f32 / 100.0,
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use ::rand::rngs::StdRng;
+use ::rand::{thread_rng, Rng, SeedableRng};
+
+use crate::bitboard::{BitBoard, Collapser};
+use crate::{check_winner, prob_to_definite, Correlations, DefinitePiece, Player, ProbPiece, WinCondition, WinRule};
+
+// 基准测试：比较 Vec 棋盘与位棋盘的坍缩和胜负判定速度（运行 `probability_gomoku --bench`）
+const BOARD_SIZE: usize = 15;
+const STONES: usize = 100;
+const SAMPLES: u32 = 20_000;
+const RULES: [WinRule; 4] = [
+    WinRule::Freestyle,
+    WinRule::Exact,
+    WinRule::BlackExact,
+    WinRule::WhiteExact,
+];
+const WIN_LENGTHS: [usize; 3] = [5, 6, 7];
+const FIVE: WinCondition = WinCondition { length: 5, rule: WinRule::Freestyle };
+
+// 固定种子生成的中盘局面
+fn sample_board() -> Vec<Vec<ProbPiece>> {
+    let mut rng = StdRng::seed_from_u64(2024);
+    let pieces = [ProbPiece::BLACK_90, ProbPiece::BLACK_70, ProbPiece::BLACK_30, ProbPiece::BLACK_10];
+    let mut board = vec![vec![ProbPiece::Empty; BOARD_SIZE]; BOARD_SIZE];
+    let mut placed = 0;
+    while placed < STONES {
+        let (row, col) = (rng.gen_range(0..BOARD_SIZE), rng.gen_range(0..BOARD_SIZE));
+        if board[row][col] == ProbPiece::Empty {
+            board[row][col] = pieces[placed % pieces.len()];
+            placed += 1;
+        }
+    }
+    board
+}
+
+fn collapse_vec(board: &[Vec<ProbPiece>]) -> Vec<Vec<DefinitePiece>> {
+    board
+        .iter()
+        .map(|line| line.iter().map(|&piece| prob_to_definite(piece)).collect())
+        .collect()
+}
+
+fn time<F: FnMut()>(mut run: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..SAMPLES {
+        run();
+    }
+    start.elapsed() / SAMPLES
+}
+
+fn report(name: &str, baseline: Duration, bitboard: Duration) {
+    println!(
+        "{:<24} vec {:>9.2?}   bitboard {:>9.2?}   speedup {:.1}x",
+        name,
+        baseline,
+        bitboard,
+        baseline.as_secs_f64() / bitboard.as_secs_f64()
+    );
+}
+
+// 两种实现的判定是否一致：整盘胜负，以及每个棋子处的增量判定与获胜连线是否吻合
+fn agrees(board: &[Vec<DefinitePiece>], win: WinCondition) -> bool {
+    let (outcome, pieces) = check_winner(board, win);
+    let bitboard = BitBoard::from_definite(board);
+    let through = |row: usize, col: usize| {
+        let black = pieces.black.iter().any(|run| run.contains(&(row, col)));
+        let white = pieces.white.iter().any(|run| run.contains(&(row, col)));
+        match (black, white) {
+            (true, _) => Some(Player::Black),
+            (_, true) => Some(Player::White),
+            _ => None,
+        }
+    };
+    let size = board.len();
+    outcome == bitboard.winner(win) &&
+        (0..size).all(|row| (0..size).all(|col| bitboard.win_through(row, col, win) == through(row, col)))
+}
+
+pub fn run() {
+    let board = sample_board();
+    println!("{}x{} board, {} stones, {} samples each", BOARD_SIZE, BOARD_SIZE, STONES, SAMPLES);
+
+    // 两种实现在每种获胜条件下的判定结果必须一致
+    let collapsed: Vec<Vec<Vec<DefinitePiece>>> = (0..SAMPLES).map(|_| collapse_vec(&board)).collect();
+    let mut mismatches = 0;
+    for length in WIN_LENGTHS {
+        for rule in RULES {
+            let win = WinCondition { length, rule };
+            let rule_mismatches = collapsed
+                .iter()
+                .filter(|board| !agrees(board, win))
+                .count();
+            println!(
+                "winner agreement ({} {:?}): {} / {}",
+                length,
+                rule,
+                SAMPLES as usize - rule_mismatches,
+                SAMPLES
+            );
+            mismatches += rule_mismatches;
+        }
+    }
+
+    let mut boards = collapsed.iter().cycle();
+    let bitboards: Vec<BitBoard> = collapsed.iter().map(|board| BitBoard::from_definite(board)).collect();
+    let mut bitboard_iter = bitboards.iter().cycle();
+    report(
+        "win check",
+        time(|| {
+            black_box(check_winner(boards.next().unwrap(), FIVE));
+        }),
+        time(|| {
+            black_box(bitboard_iter.next().unwrap().winner(FIVE));
+        }),
+    );
+
+    let collapser = Collapser::new(&board, &Correlations::default());
+    let mut target = BitBoard::new(BOARD_SIZE);
+    let mut rng = thread_rng();
+    report(
+        "collapse",
+        time(|| {
+            black_box(collapse_vec(&board));
+        }),
+        time(|| {
+            collapser.collapse_into(&mut rng, &mut target);
+            black_box(&target);
+        }),
+    );
+    report(
+        "collapse + win check",
+        time(|| {
+            black_box(check_winner(&collapse_vec(&board), FIVE));
+        }),
+        time(|| {
+            collapser.collapse_into(&mut rng, &mut target);
+            black_box(target.winner(FIVE));
+        }),
+    );
+
+    if mismatches > 0 {
+        eprintln!("bitboard winner disagrees with check_winner on {} boards", mismatches);
+        std::process::exit(1);
+    }
+}
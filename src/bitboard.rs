@@ -0,0 +1,392 @@
+use ::rand::Rng;
+
+use crate::{Correlations, DefinitePiece, Outcome, Player, ProbPiece, WinCondition};
+
+// 支持的最大棋盘路数（与对局记录的校验范围一致）
+pub const MAX_BOARD_SIZE: usize = 25;
+
+// 每行末尾留一列空位作为哨兵，横向与斜向移位时不会从上一行末尾连到下一行开头
+const MAX_STRIDE: usize = MAX_BOARD_SIZE + 1;
+const WORDS: usize = (MAX_BOARD_SIZE * MAX_STRIDE).div_ceil(64);
+
+// 定长位集：第 row * stride + col 位对应棋盘上的一个交叉点
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct Bits([u64; WORDS]);
+
+impl Bits {
+    pub fn set(&mut self, index: usize) {
+        self.0[index / 64] |= 1 << (index % 64);
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        self.0[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&word| word == 0)
+    }
+
+    pub fn count(&self) -> u32 {
+        self.0.iter().map(|word| word.count_ones()).sum()
+    }
+
+    pub fn and(&self, other: &Bits) -> Bits {
+        let mut result = *self;
+        for (word, other) in result.0.iter_mut().zip(other.0) {
+            *word &= other;
+        }
+        result
+    }
+
+    // 去掉 other 中为1的位
+    pub fn and_not(&self, other: &Bits) -> Bits {
+        let mut result = *self;
+        for (word, other) in result.0.iter_mut().zip(other.0) {
+            *word &= !other;
+        }
+        result
+    }
+
+    // 整体左移 shift 位：第 i 位的结果为原第 i - shift 位
+    pub fn shl(&self, shift: usize) -> Bits {
+        let (words, bits) = (shift / 64, shift % 64);
+        let mut result = Bits::default();
+        for index in words..WORDS {
+            let high = self.0[index - words] << bits;
+            let low = match (index - words).checked_sub(1) {
+                Some(prev) if bits > 0 => self.0[prev] >> (64 - bits),
+                _ => 0,
+            };
+            result.0[index] = high | low;
+        }
+        result
+    }
+
+    // 整体右移 shift 位：第 i 位的结果为原第 i + shift 位
+    pub fn shr(&self, shift: usize) -> Bits {
+        let (words, bits) = (shift / 64, shift % 64);
+        let mut result = Bits::default();
+        for index in 0..WORDS.saturating_sub(words) {
+            let low = self.0[index + words] >> bits;
+            let high = match self.0.get(index + words + 1) {
+                Some(&next) if bits > 0 => next << (64 - bits),
+                _ => 0,
+            };
+            result.0[index] = low | high;
+        }
+        result
+    }
+
+    // 以 step 为间隔的连续 length 位全部为1的起点集合
+    fn runs(&self, step: usize, length: usize) -> Bits {
+        let mut result = *self;
+        for offset in 1..length {
+            result = result.and(&self.shr(step * offset));
+        }
+        result
+    }
+}
+
+// 确定棋盘的位棋盘表示：黑白各一个位集
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BitBoard {
+    pub size: usize,
+    pub black: Bits,
+    pub white: Bits,
+}
+
+impl BitBoard {
+    pub fn new(size: usize) -> Self {
+        assert!(size <= MAX_BOARD_SIZE, "board size {} exceeds {}", size, MAX_BOARD_SIZE);
+        BitBoard {
+            size,
+            black: Bits::default(),
+            white: Bits::default(),
+        }
+    }
+
+    fn stride(&self) -> usize {
+        self.size + 1
+    }
+
+    pub fn index(&self, row: usize, col: usize) -> usize {
+        row * self.stride() + col
+    }
+
+    pub fn from_definite(board: &[Vec<DefinitePiece>]) -> Self {
+        let mut bitboard = BitBoard::new(board.len());
+        for (row, line) in board.iter().enumerate() {
+            for (col, &piece) in line.iter().enumerate() {
+                let index = bitboard.index(row, col);
+                match piece {
+                    DefinitePiece::Black => bitboard.black.set(index),
+                    DefinitePiece::White => bitboard.white.set(index),
+                    DefinitePiece::Empty => {}
+                }
+            }
+        }
+        bitboard
+    }
+
+    pub fn stones(&self, player: Player) -> &Bits {
+        match player {
+            Player::Black => &self.black,
+            Player::White => &self.white,
+        }
+    }
+
+    // 四个方向上相邻交叉点的位距离：横、竖、右下斜、左下斜
+    fn direction_shifts(&self) -> [usize; 4] {
+        let stride = self.stride();
+        [1, stride, stride + 1, stride - 1]
+    }
+
+    // 某一方是否有长度至少为 length 的连线（移位相与）
+    pub fn has_line(&self, player: Player, length: usize) -> bool {
+        let stones = self.stones(player);
+        self.direction_shifts()
+            .into_iter()
+            .any(|shift| !stones.runs(shift, length).is_empty())
+    }
+
+    // 某一方是否有长度恰好为 length 的连线：连线起点前一格与终点后一格都不是己方棋子
+    pub fn has_exact_line(&self, player: Player, length: usize) -> bool {
+        let stones = self.stones(player);
+        self.direction_shifts().into_iter().any(|shift| {
+            let exact = stones
+                .runs(shift, length)
+                .and_not(&stones.shl(shift))
+                .and_not(&stones.shr(shift * length));
+            !exact.is_empty()
+        })
+    }
+
+    // 按获胜条件判定某一方是否获胜
+    pub fn has_win(&self, player: Player, win: WinCondition) -> bool {
+        if win.rule.exact_length(player) {
+            self.has_exact_line(player, win.length)
+        } else {
+            self.has_line(player, win.length)
+        }
+    }
+
+    // 在空位放置一方的棋子
+    pub fn place(&mut self, row: usize, col: usize, player: Player) {
+        let index = self.index(row, col);
+        match player {
+            Player::Black => self.black.set(index),
+            Player::White => self.white.set(index),
+        }
+    }
+
+    // 增量判定：只检查经过 (row, col) 的四条线上该点所属一方是否获胜
+    //
+    // 某点变化后只有经过它的连线可能新形成获胜连线，因此落子后无需重新扫描整个棋盘。
+    pub fn win_through(&self, row: usize, col: usize, win: WinCondition) -> Option<Player> {
+        let index = self.index(row, col);
+        let player = if self.black.get(index) {
+            Player::Black
+        } else if self.white.get(index) {
+            Player::White
+        } else {
+            return None;
+        };
+        let stones = self.stones(player);
+        let total = self.size * self.stride();
+        // 哨兵列与越界位均为0，计数会自然停止；每侧最多数获胜子数枚，足以区分恰好连成与长连
+        let count = |shift: usize, forward: bool| {
+            (1..=win.length)
+                .take_while(|&step| {
+                    let offset = shift * step;
+                    if forward {
+                        index + offset < total && stones.get(index + offset)
+                    } else {
+                        offset <= index && stones.get(index - offset)
+                    }
+                })
+                .count()
+        };
+        self.direction_shifts()
+            .into_iter()
+            .any(|shift| win.wins(player, 1 + count(shift, true) + count(shift, false)))
+            .then_some(player)
+    }
+
+    // 与 check_winner 相同的胜负判定，但不记录获胜连线
+    pub fn winner(&self, win: WinCondition) -> Option<Outcome> {
+        match (self.has_win(Player::Black, win), self.has_win(Player::White, win)) {
+            (true, true) => Some(Outcome::BothWin),
+            (true, false) => Some(Outcome::Win(Player::Black)),
+            (false, true) => Some(Outcome::Win(Player::White)),
+            (false, false) => {
+                let full = (self.black.count() + self.white.count()) as usize == self.size * self.size;
+                full.then_some(Outcome::BoardFull)
+            }
+        }
+    }
+}
+
+// 概率棋盘的坍缩器：预先记录所有棋子的位置与黑棋概率，之后每次坍缩不再分配内存
+#[derive(Clone, Debug)]
+pub struct Collapser {
+    size: usize,
+    stones: Vec<(usize, u8)>,         // 位下标与黑棋百分比（纠缠对的第二枚与叠加棋子除外）
+    superposed: Vec<([usize; 2], u8)>, // 叠加棋子：两个候选点的位下标与黑棋百分比
+    links: Vec<(usize, usize, bool)>, // 纠缠对：第一枚与第二枚的位下标、是否同色
+}
+
+impl Collapser {
+    pub fn new(board: &[Vec<ProbPiece>], correlations: &Correlations) -> Self {
+        let template = BitBoard::new(board.len());
+        let entanglements = &correlations.entanglements;
+        let stones = board
+            .iter()
+            .enumerate()
+            .flat_map(|(row, line)| line.iter().enumerate().map(move |(col, &piece)| (row, col, piece)))
+            .filter(|&(row, col, piece)| {
+                matches!(piece, ProbPiece::Stone(_)) && !entanglements.iter().any(|pair| pair.second == (row, col))
+            })
+            .map(|(row, col, piece)| (template.index(row, col), piece.black_percent()))
+            .collect();
+        let superposed = correlations
+            .superpositions
+            .iter()
+            .map(|stone| {
+                let [(row, col), (other_row, other_col)] = stone.points;
+                let indices = [template.index(row, col), template.index(other_row, other_col)];
+                (indices, board[row][col].black_percent())
+            })
+            .collect();
+        let links = entanglements
+            .iter()
+            .map(|pair| {
+                let ((row, col), (second_row, second_col)) = (pair.first, pair.second);
+                (template.index(row, col), template.index(second_row, second_col), pair.same)
+            })
+            .collect();
+        Collapser { size: board.len(), stones, superposed, links }
+    }
+
+    // 随机坍缩到给定位棋盘（覆盖原有内容）
+    pub fn collapse_into<R: Rng>(&self, rng: &mut R, target: &mut BitBoard) {
+        *target = BitBoard::new(self.size);
+        for &(index, percent) in &self.stones {
+            if rng.gen_range(0..100) < percent {
+                target.black.set(index);
+            } else {
+                target.white.set(index);
+            }
+        }
+        // 叠加棋子先决定出现在哪一点，再决定颜色
+        for &(indices, percent) in &self.superposed {
+            let index = indices[rng.gen_range(0..2)];
+            if rng.gen_range(0..100) < percent {
+                target.black.set(index);
+            } else {
+                target.white.set(index);
+            }
+        }
+        for &(first, second, same) in &self.links {
+            if target.black.get(first) == same {
+                target.black.set(second);
+            } else {
+                target.white.set(second);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::rand::rngs::StdRng;
+    use ::rand::SeedableRng;
+
+    use super::*;
+    use crate::{check_winner, WinRule};
+
+    const FREESTYLE: WinCondition = WinCondition { length: 5, rule: WinRule::Freestyle };
+    const EXACT: WinCondition = WinCondition { length: 5, rule: WinRule::Exact };
+
+    fn board_with(size: usize, black: &[(usize, usize)], white: &[(usize, usize)]) -> Vec<Vec<DefinitePiece>> {
+        let mut board = vec![vec![DefinitePiece::Empty; size]; size];
+        for &(row, col) in black {
+            board[row][col] = DefinitePiece::Black;
+        }
+        for &(row, col) in white {
+            board[row][col] = DefinitePiece::White;
+        }
+        board
+    }
+
+    // 每个棋子处的增量判定都应与 check_winner 找到的获胜连线一致
+    fn assert_agrees(board: &[Vec<DefinitePiece>], win: WinCondition) {
+        let bitboard = BitBoard::from_definite(board);
+        let (outcome, pieces) = check_winner(board, win);
+        assert_eq!(bitboard.winner(win), outcome);
+        for (row, line) in board.iter().enumerate() {
+            for (col, &piece) in line.iter().enumerate() {
+                let (player, runs) = match piece {
+                    DefinitePiece::Black => (Player::Black, &pieces.black),
+                    DefinitePiece::White => (Player::White, &pieces.white),
+                    DefinitePiece::Empty => {
+                        assert_eq!(bitboard.win_through(row, col, win), None);
+                        continue;
+                    }
+                };
+                let expected = runs.iter().any(|run| run.contains(&(row, col))).then_some(player);
+                assert_eq!(bitboard.win_through(row, col, win), expected, "({}, {}) {:?}", row, col, win);
+            }
+        }
+    }
+
+    #[test]
+    fn win_through_at_edges() {
+        let size = 9;
+        // 右边缘的横向五连
+        let edge_five = board_with(size, &[(0, 4), (0, 5), (0, 6), (0, 7), (0, 8)], &[]);
+        assert_eq!(BitBoard::from_definite(&edge_five).win_through(0, 8, FREESTYLE), Some(Player::Black));
+        assert_agrees(&edge_five, FREESTYLE);
+        assert_agrees(&edge_five, EXACT);
+        // 行尾接下一行行首：哨兵列阻断，不算连线
+        let wrapped = board_with(size, &[(2, 6), (2, 7), (2, 8), (3, 0), (3, 1)], &[]);
+        assert_eq!(BitBoard::from_definite(&wrapped).win_through(2, 8, FREESTYLE), None);
+        assert_agrees(&wrapped, FREESTYLE);
+        // 左下斜经过左边缘后不应接到上一行行尾
+        let anti = board_with(size, &[(4, 2), (5, 1), (6, 0), (6, 8), (7, 7)], &[]);
+        assert_eq!(BitBoard::from_definite(&anti).win_through(6, 0, FREESTYLE), None);
+        assert_agrees(&anti, FREESTYLE);
+        // 贴底边的竖向长连：自由规则获胜，恰好五子规则不算
+        let overline: Vec<(usize, usize)> = (3..9).map(|row| (row, 8)).collect();
+        let overline = board_with(size, &overline, &[(0, 0)]);
+        assert_eq!(BitBoard::from_definite(&overline).win_through(8, 8, FREESTYLE), Some(Player::Black));
+        assert_eq!(BitBoard::from_definite(&overline).win_through(8, 8, EXACT), None);
+        assert_agrees(&overline, FREESTYLE);
+        assert_agrees(&overline, EXACT);
+        // 白方在角上的斜向五连
+        let corner: Vec<(usize, usize)> = (4..9).map(|step| (step, step)).collect();
+        let corner = board_with(size, &[], &corner);
+        assert_agrees(&corner, EXACT);
+    }
+
+    #[test]
+    fn win_through_matches_check_winner() {
+        let mut rng = StdRng::seed_from_u64(39);
+        let rules = [WinRule::Freestyle, WinRule::Exact, WinRule::BlackExact, WinRule::WhiteExact];
+        for round in 0..400 {
+            let size = [5, 6, 7, 9][round % 4];
+            let length = [4, 5][round % 2];
+            let board: Vec<Vec<DefinitePiece>> = (0..size)
+                .map(|_| {
+                    (0..size)
+                        .map(|_| match rng.gen_range(0..5) {
+                            0 => DefinitePiece::Empty,
+                            1 => DefinitePiece::White,
+                            _ => DefinitePiece::Black,
+                        })
+                        .collect()
+                })
+                .collect();
+            assert_agrees(&board, WinCondition { length, rule: rules[round % rules.len()] });
+        }
+    }
+}
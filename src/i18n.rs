@@ -0,0 +1,622 @@
+use std::fmt::Display;
+
+// 界面语言
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    English,
+    SimplifiedChinese,
+}
+
+pub const LANGUAGES: [Language; 2] = [Language::English, Language::SimplifiedChinese];
+
+impl Language {
+    // 设置文件中使用的语言代码
+    pub fn key(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::SimplifiedChinese => "zh-CN",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Language> {
+        match key {
+            "en" => Some(Language::English),
+            "zh-CN" => Some(Language::SimplifiedChinese),
+            _ => None,
+        }
+    }
+
+    // 按系统语言环境选择默认语言
+    pub fn from_system() -> Language {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+            .unwrap_or_default();
+        if locale.starts_with("zh") {
+            Language::SimplifiedChinese
+        } else {
+            Language::English
+        }
+    }
+
+    // 是否需要支持中日韩文字的字体
+    pub fn needs_cjk_font(self) -> bool {
+        self == Language::SimplifiedChinese
+    }
+}
+
+// 界面文本目录：每种语言一份，模板中的 {} 由 fill 依次替换
+pub struct Strings {
+    pub language_name: &'static str,
+
+    // 对局界面
+    pub current_turn_black: &'static str,
+    pub current_turn_white: &'static str,
+    pub next_black_90: &'static str,
+    pub next_black_70: &'static str,
+    pub next_white_90: &'static str,
+    pub next_white_70: &'static str,
+    pub preview_board: &'static str,
+    pub hide_preview: &'static str,
+    pub previews_left: &'static str,
+    pub end_turn: &'static str,
+    pub confirm_end_turn: &'static str,
+    pub hint_end_turn: &'static str,
+    pub hint_place: &'static str,
+    pub hint_place_n: &'static str,
+    pub hint_computer: &'static str,
+    pub hint_restart: &'static str,
+    pub opening_first_places: &'static str,
+    pub opening_second_places: &'static str,
+    pub opening_second_chooses: &'static str,
+    pub opening_first_chooses: &'static str,
+    pub play_black: &'static str,
+    pub play_white: &'static str,
+    pub place_two_more: &'static str,
+    pub skip_animation: &'static str,
+
+    // 棋子悬浮提示
+    pub tooltip_probability: &'static str,
+    pub tooltip_placed: &'static str,
+    pub tooltip_lines: &'static str,
+    pub tooltip_line: &'static str,
+    pub line_horizontal: &'static str,
+    pub line_vertical: &'static str,
+    pub line_diagonal: &'static str,
+    pub line_anti_diagonal: &'static str,
+    pub menu: &'static str,
+    pub restart_game: &'static str,
+    pub exit_game: &'static str,
+    pub black: &'static str,
+    pub white: &'static str,
+
+    // 对局结果
+    pub black_wins: &'static str,
+    pub white_wins: &'static str,
+    pub both_win: &'static str,
+    pub board_full: &'static str,
+    pub black_wins_on_time: &'static str,
+    pub white_wins_on_time: &'static str,
+
+    // 规则说明
+    pub rules_title: &'static str,
+    pub rule_turns: &'static str,
+    pub stones_one: &'static str,
+    pub stones_n: &'static str,
+    pub stones_connect6: &'static str,
+    pub rule_black_rotate: &'static str,
+    pub rule_white_rotate: &'static str,
+    pub rule_black_strong: &'static str,
+    pub rule_white_strong: &'static str,
+    pub rule_black_weak: &'static str,
+    pub rule_white_weak: &'static str,
+    pub rule_preview: &'static str,
+    pub rule_preview_per_game: &'static str,
+    pub rule_double_win_observer_loses: &'static str,
+    pub rule_double_win_observer_wins: &'static str,
+    pub rule_double_win_count_lines: &'static str,
+    pub rule_double_win_reroll: &'static str,
+    pub rule_preview_bank: &'static str,
+    pub rule_preview_costs: &'static str,
+    pub times_once: &'static str,
+    pub times_twice: &'static str,
+    pub times_n: &'static str,
+    pub rule_win: &'static str,
+    pub rule_win_exact: &'static str,
+    pub rule_win_black_exact: &'static str,
+    pub rule_win_white_exact: &'static str,
+    pub rule_renju: &'static str,
+    pub rule_opening_swap: &'static str,
+    pub rule_opening_swap2: &'static str,
+    pub rule_opening_prob_swap: &'static str,
+    pub rule_keys: &'static str,
+
+    // 菜单
+    pub game_title: &'static str,
+    pub new_game: &'static str,
+    pub load_game: &'static str,
+    pub replay_last_game: &'static str,
+    pub settings: &'static str,
+    pub quit: &'static str,
+    pub start_game: &'static str,
+    pub back: &'static str,
+    pub opponent: &'static str,
+    pub opponent_human: &'static str,
+    pub opponent_computer_white: &'static str,
+    pub opponent_computer_black: &'static str,
+    pub board: &'static str,
+    pub pieces: &'static str,
+    pub tiers_rotate: &'static str,
+    pub tiers_strong: &'static str,
+    pub tiers_weak: &'static str,
+    pub previews: &'static str,
+    pub preview_rule: &'static str,
+    pub double_win: &'static str,
+    pub double_win_draw: &'static str,
+    pub double_win_observer_loses: &'static str,
+    pub double_win_observer_wins: &'static str,
+    pub double_win_count_lines: &'static str,
+    pub double_win_reroll: &'static str,
+    pub preview_rule_per_turn: &'static str,
+    pub preview_rule_per_game: &'static str,
+    pub preview_rule_bank: &'static str,
+    pub preview_rule_costs: &'static str,
+    pub time_control: &'static str,
+    pub time_minutes: &'static str,
+    pub time_fischer: &'static str,
+    pub time_byo_yomi: &'static str,
+    pub time_main_byo_yomi: &'static str,
+    pub pause: &'static str,
+    pub entangle: &'static str,
+    pub cancel: &'static str,
+    pub hint_pick_first: &'static str,
+    pub hint_pick_second: &'static str,
+    pub entangle_rule: &'static str,
+    pub superpose: &'static str,
+    pub superposition: &'static str,
+    pub rule_superposition: &'static str,
+    pub tooltip_superposed: &'static str,
+    pub power_cards: &'static str,
+    pub power_cards_each: &'static str,
+    pub card_remove: &'static str,
+    pub card_flip: &'static str,
+    pub card_collapse: &'static str,
+    pub hint_pick_card: &'static str,
+    pub rule_power_cards: &'static str,
+    pub entangle_opposite: &'static str,
+    pub entangle_same: &'static str,
+    pub rule_entangle_opposite: &'static str,
+    pub rule_entangle_same: &'static str,
+    pub resume: &'static str,
+    pub paused: &'static str,
+    pub rule_time: &'static str,
+    pub rule_drift_even: &'static str,
+    pub rule_drift_owner: &'static str,
+    pub drift: &'static str,
+    pub drift_even: &'static str,
+    pub drift_owner: &'static str,
+    pub rule_time_fischer: &'static str,
+    pub rule_time_byo_yomi: &'static str,
+    pub win_rule: &'static str,
+    pub win_rule_freestyle: &'static str,
+    pub win_rule_exact: &'static str,
+    pub win_rule_black_exact: &'static str,
+    pub win_rule_white_exact: &'static str,
+    pub win_length: &'static str,
+    pub stones_per_turn: &'static str,
+    pub stones_per_turn_connect6: &'static str,
+    pub renju: &'static str,
+    pub opening: &'static str,
+    pub opening_standard: &'static str,
+    pub opening_swap: &'static str,
+    pub opening_swap2: &'static str,
+    pub opening_prob_swap: &'static str,
+
+    // 设置
+    pub theme: &'static str,
+    pub language: &'static str,
+    pub sound: &'static str,
+    pub volume: &'static str,
+    pub probability_text: &'static str,
+    pub split_stones: &'static str,
+    pub coordinates: &'static str,
+    pub confirm_end_turn_setting: &'static str,
+    pub animation_speed: &'static str,
+    pub on: &'static str,
+    pub off: &'static str,
+    pub speed_slow: &'static str,
+    pub speed_normal: &'static str,
+    pub speed_fast: &'static str,
+
+    // 复盘
+    pub replay_move: &'static str,
+    pub replay_prev: &'static str,
+    pub replay_next: &'static str,
+}
+
+pub static EN: Strings = Strings {
+    language_name: "English",
+
+    current_turn_black: "Current Turn: Black",
+    current_turn_white: "Current Turn: White",
+    next_black_90: "Next Piece: 90% Black",
+    next_black_70: "Next Piece: 70% Black",
+    next_white_90: "Next Piece: 90% White (10% Black)",
+    next_white_70: "Next Piece: 70% White (30% Black)",
+    preview_board: "Preview Board",
+    hide_preview: "Hide Preview",
+    previews_left: "Previews Left: {}",
+    end_turn: "End Turn",
+    confirm_end_turn: "Confirm?",
+    hint_end_turn: "Click End Turn",
+    hint_place: "Place a Piece",
+    hint_place_n: "Place {} Pieces",
+    hint_computer: "Computer Thinking",
+    hint_restart: "Press R Again to Restart",
+    opening_first_places: "Opening: First Player Places {}",
+    opening_second_places: "Opening: Second Player Places {}",
+    opening_second_chooses: "Second Player: Choose a Colour",
+    opening_first_chooses: "First Player: Choose a Colour",
+    play_black: "Play Black",
+    play_white: "Play White",
+    place_two_more: "Place Two More",
+    skip_animation: "Press Enter to skip",
+
+    tooltip_probability: "Black {}% / White {}%",
+    tooltip_placed: "Placed by {} on move {}",
+    tooltip_lines: "Chance of a winning line on collapse:",
+    tooltip_line: "  {}: {}%",
+    line_horizontal: "Row",
+    line_vertical: "Column",
+    line_diagonal: "Diagonal \\",
+    line_anti_diagonal: "Diagonal /",
+    menu: "Menu",
+    restart_game: "Restart Game",
+    exit_game: "Exit Game",
+    black: "Black",
+    white: "White",
+
+    black_wins: "Black Wins!",
+    white_wins: "White Wins!",
+    both_win: "Draw! Both Players Win!",
+    board_full: "Draw! Board Full!",
+    black_wins_on_time: "Black Wins on Time!",
+    white_wins_on_time: "White Wins on Time!",
+
+    rules_title: "Game Rules",
+    rule_turns: "Black goes first. Players take turns: {}.",
+    stones_one: "1 piece per turn",
+    stones_n: "{} pieces per turn",
+    stones_connect6: "1 piece on the first turn, then 2 per turn",
+    rule_black_rotate: "Black's pieces: 90% Black / 70% Black (rotates each turn)",
+    rule_white_rotate: "White's pieces: 90% White / 70% White (rotates each turn)",
+    rule_black_strong: "Black's pieces: always 90% Black",
+    rule_white_strong: "White's pieces: always 90% White",
+    rule_black_weak: "Black's pieces: always 70% Black",
+    rule_white_weak: "White's pieces: always 70% White",
+    rule_preview: "Click 'Preview Board' to see final pieces {} per turn.",
+    rule_double_win_observer_loses: "If both colours win on a preview, the previewing player loses.",
+    rule_double_win_observer_wins: "If both colours win on a preview, the previewing player wins.",
+    rule_double_win_count_lines: "If both colours win on a preview, the colour with more winning lines wins.",
+    rule_double_win_reroll: "If both colours win on a preview, the board collapses again.",
+    rule_preview_per_game: "Click 'Preview Board' to see final pieces {} per game.",
+    rule_preview_bank: "Click 'Preview Board' to see final pieces {} per turn; unused previews carry over (up to {}).",
+    rule_preview_costs: "Click 'Preview Board' to see final pieces {} per turn, instead of placing.",
+    times_once: "once",
+    times_twice: "twice",
+    times_n: "{} times",
+    rule_win: "Win by getting {} or more same pieces in a row after preview.",
+    rule_win_exact: "Win by getting exactly {} same pieces in a row after preview; longer lines do not count.",
+    rule_win_black_exact: "Win by getting {} in a row after preview; Black needs exactly that many, White may have more.",
+    rule_win_white_exact: "Win by getting {} in a row after preview; White needs exactly that many, Black may have more.",
+    rule_renju: "Renju: Black may not play 3-3, 4-4 or overline points (x), judged by each stone's likelier colour.",
+    rule_opening_swap: "Swap: the first player places Black, White, Black; the second player then picks a colour.",
+    rule_opening_swap2: "Swap2: after three stones the second player picks a colour or places two more for the first player to choose.",
+    rule_opening_prob_swap: "Probability Swap: as Swap, but the three opening stones are all 70% stones.",
+    rule_keys: "Keys: Arrows/WASD move, Enter place or skip animation, Space end turn, P preview, E entangle, Q superpose, 1/2/3 power cards, Tab pause, R restart, Esc menu.",
+
+    game_title: "Probability Gomoku",
+    new_game: "New Game",
+    load_game: "Load Game",
+    replay_last_game: "Replay Last Game",
+    settings: "Settings",
+    quit: "Quit",
+    start_game: "Start Game",
+    back: "Back",
+    opponent: "Opponent: {}",
+    opponent_human: "Human (hot-seat)",
+    opponent_computer_white: "Computer plays White",
+    opponent_computer_black: "Computer plays Black",
+    board: "Board: {} x {}",
+    pieces: "Pieces: {}",
+    tiers_rotate: "90% / 70% rotating",
+    tiers_strong: "90% only",
+    tiers_weak: "70% only",
+    previews: "Previews: {}",
+    preview_rule: "Preview Rule: {}",
+    double_win: "Both Win: {}",
+    double_win_draw: "Draw",
+    double_win_observer_loses: "Previewer Loses",
+    double_win_observer_wins: "Previewer Wins",
+    double_win_count_lines: "More Lines Wins",
+    double_win_reroll: "Collapse Again",
+    preview_rule_per_turn: "Per Turn",
+    preview_rule_per_game: "Per Game",
+    preview_rule_bank: "Bankable",
+    preview_rule_costs: "Instead of Placing",
+    time_control: "Time Control: {}",
+    time_minutes: "{} min each",
+    time_fischer: "{} min + {}s per turn",
+    time_byo_yomi: "Byo-yomi {} x {}s",
+    time_main_byo_yomi: "{} min + {} x {}s",
+    pause: "Pause",
+    entangle: "Entangle",
+    cancel: "Cancel",
+    hint_pick_first: "Pick the first point",
+    hint_pick_second: "Pick the second point",
+    entangle_rule: "Entangled Pairs: {}",
+    superpose: "Superpose",
+    superposition: "Superposition: {}",
+    rule_superposition: "'Superpose' (Q) places one stone on two points; on collapse it appears at only one of them.",
+    tooltip_superposed: "Superposed: 50% chance to appear here",
+    power_cards: "Power Cards: {}",
+    power_cards_each: "{} of Each",
+    card_remove: "Remove ({})",
+    card_flip: "Flip ({})",
+    card_collapse: "Collapse ({})",
+    hint_pick_card: "Pick a stone",
+    rule_power_cards: "Power cards (1/2/3), one per turn before finishing: remove an opponent's stone, flip a stone's odds, or collapse one stone.",
+    entangle_opposite: "Opposite Colours",
+    entangle_same: "Same Colour",
+    rule_entangle_opposite: "'Entangle' (E) places two linked stones as one move; they collapse to opposite colours.",
+    rule_entangle_same: "'Entangle' (E) places two linked stones as one move; they collapse to the same colour.",
+    resume: "Resume",
+    paused: "Paused",
+    rule_drift_even: "After each turn, earlier stones drift {}% toward 50%.",
+    rule_drift_owner: "After each turn, earlier stones drift {}% toward their owner's colour.",
+    drift: "Drift: {}",
+    drift_even: "{}% to 50%",
+    drift_owner: "{}% to Owner",
+    rule_time: "Each player has {} min; running out of time loses. Tab pauses.",
+    rule_time_fischer: "Each player has {} min and gains {}s after each turn. Tab pauses.",
+    rule_time_byo_yomi: "After {} min, each turn has {}s; overrunning it {} times loses. Tab pauses.",
+    win_rule: "Overlines: {}",
+    win_rule_freestyle: "Freestyle (overlines win)",
+    win_rule_exact: "Exact length only",
+    win_rule_black_exact: "Exact length for Black",
+    win_rule_white_exact: "Exact length for White",
+    win_length: "Win Length: {} in a Row",
+    stones_per_turn: "Stones per Turn: {}",
+    stones_per_turn_connect6: "Connect6 (1, then 2)",
+    renju: "Renju Forbidden Moves: {}",
+    opening: "Opening: {}",
+    opening_standard: "Standard",
+    opening_swap: "Swap",
+    opening_swap2: "Swap2",
+    opening_prob_swap: "Probability Swap",
+
+    theme: "Theme: {}",
+    language: "Language: {}",
+    sound: "Sound: {}",
+    volume: "Volume: {}%",
+    probability_text: "Probability Text: {}",
+    split_stones: "Split Stones: {}",
+    coordinates: "Coordinates: {}",
+    confirm_end_turn_setting: "Confirm End Turn: {}",
+    animation_speed: "Animation Speed: {}",
+    on: "On",
+    off: "Off",
+    speed_slow: "Slow",
+    speed_normal: "Normal",
+    speed_fast: "Fast",
+
+    replay_move: "Replay - Move {} / {}",
+    replay_prev: "< Prev",
+    replay_next: "Next >",
+};
+
+pub static ZH_CN: Strings = Strings {
+    language_name: "简体中文",
+
+    current_turn_black: "当前回合：黑方",
+    current_turn_white: "当前回合：白方",
+    next_black_90: "下一手：90% 黑",
+    next_black_70: "下一手：70% 黑",
+    next_white_90: "下一手：90% 白（10% 黑）",
+    next_white_70: "下一手：70% 白（30% 黑）",
+    preview_board: "预览棋盘",
+    hide_preview: "隐藏预览",
+    previews_left: "剩余预览：{}",
+    end_turn: "结束回合",
+    confirm_end_turn: "确认结束？",
+    hint_end_turn: "请结束回合",
+    hint_place: "请落子",
+    hint_place_n: "请再落{}子",
+    hint_computer: "电脑思考中",
+    hint_restart: "再按 R 重新开始",
+    opening_first_places: "开局：先手方摆{}",
+    opening_second_places: "开局：后手方摆{}",
+    opening_second_chooses: "后手方：请选择颜色",
+    opening_first_chooses: "先手方：请选择颜色",
+    play_black: "执黑",
+    play_white: "执白",
+    place_two_more: "再摆两子",
+    skip_animation: "按回车跳过动画",
+
+    tooltip_probability: "黑 {}% / 白 {}%",
+    tooltip_placed: "{}第 {} 手落子",
+    tooltip_lines: "坍缩后连成获胜连线的概率：",
+    tooltip_line: "  {}：{}%",
+    line_horizontal: "横线",
+    line_vertical: "竖线",
+    line_diagonal: "斜线 \\",
+    line_anti_diagonal: "斜线 /",
+    menu: "菜单",
+    restart_game: "重新开始",
+    exit_game: "退出游戏",
+    black: "黑方",
+    white: "白方",
+
+    black_wins: "黑方获胜！",
+    white_wins: "白方获胜！",
+    both_win: "平局！双方同时成五！",
+    board_full: "平局！棋盘已满！",
+    black_wins_on_time: "白方超时，黑方获胜！",
+    white_wins_on_time: "黑方超时，白方获胜！",
+
+    rules_title: "游戏规则",
+    rule_turns: "黑方先行，双方轮流落子：{}。",
+    stones_one: "每回合一子",
+    stones_n: "每回合{}子",
+    stones_connect6: "首回合一子，之后每回合两子",
+    rule_black_rotate: "黑方棋子：90% 黑 / 70% 黑（每回合轮换）",
+    rule_white_rotate: "白方棋子：90% 白 / 70% 白（每回合轮换）",
+    rule_black_strong: "黑方棋子：固定为 90% 黑",
+    rule_white_strong: "白方棋子：固定为 90% 白",
+    rule_black_weak: "黑方棋子：固定为 70% 黑",
+    rule_white_weak: "白方棋子：固定为 70% 白",
+    rule_preview: "点击“预览棋盘”查看坍缩结果，每回合{}。",
+    rule_double_win_observer_loses: "预览时双方同时成五，预览的一方判负。",
+    rule_double_win_observer_wins: "预览时双方同时成五，预览的一方获胜。",
+    rule_double_win_count_lines: "预览时双方同时成五，获胜连线多的一方获胜。",
+    rule_double_win_reroll: "预览时双方同时成五，重新坍缩直到分出胜负。",
+    rule_preview_per_game: "点击“预览棋盘”查看坍缩结果，每局{}。",
+    rule_preview_bank: "点击“预览棋盘”查看坍缩结果，每回合{}，未用的次数可累积（最多{}次）。",
+    rule_preview_costs: "点击“预览棋盘”查看坍缩结果，每回合{}，但预览后本回合不能落子。",
+    times_once: "一次",
+    times_twice: "两次",
+    times_n: "{}次",
+    rule_win: "预览时同色{}子或以上连珠即获胜。",
+    rule_win_exact: "预览时同色恰好{}子连珠才获胜，更长的长连不算。",
+    rule_win_black_exact: "预览时{}子连珠获胜；黑方须恰好连成，白方长连也算。",
+    rule_win_white_exact: "预览时{}子连珠获胜；白方须恰好连成，黑方长连也算。",
+    rule_renju: "连珠禁手：黑方不得落在三三、四四、长连点（×），按每枚棋子较可能的颜色判断。",
+    rule_opening_swap: "交换开局：先手方依次摆黑、白、黑三子，后手方再选择执黑或执白。",
+    rule_opening_swap2: "二次交换：摆完三子后，后手方选择颜色，或再摆两子由先手方选择。",
+    rule_opening_prob_swap: "概率交换：同交换开局，但开局三子均为 70% 棋子。",
+    rule_keys: "按键：方向键/WASD 移动，回车落子或跳过动画，空格结束回合，P 预览，E 纠缠落子，Q 叠加落子，1/2/3 能力卡，Tab 暂停，R 重新开始，Esc 菜单。",
+
+    game_title: "概率五子棋",
+    new_game: "新游戏",
+    load_game: "继续对局",
+    replay_last_game: "复盘上一局",
+    settings: "设置",
+    quit: "退出",
+    start_game: "开始游戏",
+    back: "返回",
+    opponent: "对手：{}",
+    opponent_human: "真人（同屏对战）",
+    opponent_computer_white: "电脑执白",
+    opponent_computer_black: "电脑执黑",
+    board: "棋盘：{} x {}",
+    pieces: "棋子：{}",
+    tiers_rotate: "90% / 70% 轮换",
+    tiers_strong: "仅 90%",
+    tiers_weak: "仅 70%",
+    previews: "预览次数：{}",
+    preview_rule: "预览规则：{}",
+    double_win: "双方成五：{}",
+    double_win_draw: "平局",
+    double_win_observer_loses: "预览方判负",
+    double_win_observer_wins: "预览方获胜",
+    double_win_count_lines: "连线多者胜",
+    double_win_reroll: "重新坍缩",
+    preview_rule_per_turn: "每回合",
+    preview_rule_per_game: "每局",
+    preview_rule_bank: "可累积",
+    preview_rule_costs: "代替落子",
+    time_control: "用时：{}",
+    time_minutes: "每方{}分钟",
+    time_fischer: "{}分钟，每回合加{}秒",
+    time_byo_yomi: "读秒{}次，每次{}秒",
+    time_main_byo_yomi: "{}分钟+读秒{}次{}秒",
+    pause: "暂停",
+    entangle: "纠缠落子",
+    cancel: "取消",
+    hint_pick_first: "请选择第一个点",
+    hint_pick_second: "请选择第二个点",
+    entangle_rule: "纠缠落子：{}",
+    superpose: "叠加落子",
+    superposition: "叠加落子：{}",
+    rule_superposition: "“叠加落子”（Q）把一枚棋子同时落在两个点，坍缩时只出现在其中一点。",
+    tooltip_superposed: "叠加棋子：有50%的机会出现在此处",
+    power_cards: "能力卡：{}",
+    power_cards_each: "每种{}张",
+    card_remove: "移除（{}）",
+    card_flip: "翻转（{}）",
+    card_collapse: "坍缩（{}）",
+    hint_pick_card: "请选择棋子",
+    rule_power_cards: "能力卡（1/2/3）每回合限用一张，须在落满之前：移除对方一枚棋子、翻转一枚棋子的概率或单独坍缩一枚棋子。",
+    entangle_opposite: "颜色相反",
+    entangle_same: "颜色相同",
+    rule_entangle_opposite: "“纠缠落子”（E）一手落下两枚相连的棋子，坍缩时颜色相反。",
+    rule_entangle_same: "“纠缠落子”（E）一手落下两枚相连的棋子，坍缩时颜色相同。",
+    resume: "继续",
+    paused: "已暂停",
+    rule_drift_even: "每回合结束时，之前落下的棋子向50%漂移{}%。",
+    rule_drift_owner: "每回合结束时，之前落下的棋子向落子方颜色漂移{}%。",
+    drift: "概率漂移：{}",
+    drift_even: "每回合{}%趋向50%",
+    drift_owner: "每回合{}%趋向落子方",
+    rule_time: "每方用时{}分钟，用完判负。按 Tab 暂停。",
+    rule_time_fischer: "每方用时{}分钟，每走完一回合加{}秒。按 Tab 暂停。",
+    rule_time_byo_yomi: "{}分钟用完后每回合限时{}秒，超时{}次判负。按 Tab 暂停。",
+    win_rule: "长连规则：{}",
+    win_rule_freestyle: "自由（长连也算）",
+    win_rule_exact: "须恰好连成",
+    win_rule_black_exact: "黑方须恰好连成",
+    win_rule_white_exact: "白方须恰好连成",
+    win_length: "获胜子数：{}子连珠",
+    stones_per_turn: "每回合落子：{}",
+    stones_per_turn_connect6: "六子棋（首回合一子，之后两子）",
+    renju: "连珠禁手：{}",
+    opening: "开局规则：{}",
+    opening_standard: "标准",
+    opening_swap: "交换",
+    opening_swap2: "二次交换",
+    opening_prob_swap: "概率交换",
+
+    theme: "主题：{}",
+    language: "语言：{}",
+    sound: "音效：{}",
+    volume: "音量：{}%",
+    probability_text: "概率文字：{}",
+    split_stones: "分色棋子：{}",
+    coordinates: "棋盘坐标：{}",
+    confirm_end_turn_setting: "结束回合需确认：{}",
+    animation_speed: "动画速度：{}",
+    on: "开",
+    off: "关",
+    speed_slow: "慢",
+    speed_normal: "正常",
+    speed_fast: "快",
+
+    replay_move: "复盘 - 第 {} 手 / 共 {} 手",
+    replay_prev: "< 上一步",
+    replay_next: "下一步 >",
+};
+
+// 获取指定语言的文本目录
+pub fn strings(language: Language) -> &'static Strings {
+    match language {
+        Language::English => &EN,
+        Language::SimplifiedChinese => &ZH_CN,
+    }
+}
+
+// 依次用参数替换模板中的 {}
+pub fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut rest = template;
+    while let Some(index) = rest.find("{}") {
+        result.push_str(&rest[..index]);
+        match args.next() {
+            Some(arg) => result.push_str(&arg.to_string()),
+            None => result.push_str("{}"),
+        }
+        rest = &rest[index + 2..];
+    }
+    result.push_str(rest);
+    result
+}
@@ -0,0 +1,104 @@
+use macroquad::prelude::*;
+
+// 参考设计尺寸：所有界面元素以此为基准按比例缩放
+const BASE_WIDTH: f32 = 800.0;
+const BASE_HEIGHT: f32 = 890.0;
+const BASE_BOARD_EXTENT: f32 = 420.0;
+const BASE_BOARD_TOP: f32 = 256.0;
+
+// 界面布局：每帧根据窗口的逻辑尺寸重新计算棋盘与界面的位置
+#[derive(Clone, Copy, Debug)]
+pub struct Layout {
+    pub scale: f32,        // 相对参考设计的缩放比例
+    pub left: f32,         // 内容区域左边界
+    pub top: f32,          // 内容区域上边界
+    pub width: f32,        // 内容区域宽度
+    pub board_size: usize, // 棋盘路数
+    pub board_x: f32,      // 棋盘左上角交叉点横坐标
+    pub board_y: f32,      // 棋盘左上角交叉点纵坐标
+    pub board_extent: f32, // 棋盘网格边长
+    pub cell_size: f32,    // 格子边长
+    pub piece_radius: f32, // 棋子半径
+}
+
+impl Layout {
+    // 根据当前窗口尺寸计算布局（窗口可自由缩放，高DPI下使用逻辑像素）
+    pub fn new(board_size: usize) -> Self {
+        Self::for_screen(screen_width(), screen_height(), board_size)
+    }
+
+    // 根据指定的屏幕尺寸计算布局：等比缩放参考设计并在窗口中居中
+    pub fn for_screen(screen_w: f32, screen_h: f32, board_size: usize) -> Self {
+        let scale = (screen_w / BASE_WIDTH).min(screen_h / BASE_HEIGHT).max(0.1);
+        let width = BASE_WIDTH * scale;
+        let height = BASE_HEIGHT * scale;
+        let left = (screen_w - width) / 2.0;
+        let top = (screen_h - height) / 2.0;
+
+        let board_extent = BASE_BOARD_EXTENT * scale;
+        let cell_size = board_extent / (board_size.max(2) - 1) as f32;
+
+        Layout {
+            scale,
+            left,
+            top,
+            width,
+            board_size,
+            board_x: left + (width - board_extent) / 2.0,
+            board_y: top + BASE_BOARD_TOP * scale,
+            board_extent,
+            cell_size,
+            piece_radius: cell_size / 2.0,
+        }
+    }
+
+    // 内容区域水平中心
+    pub fn center_x(&self) -> f32 {
+        self.left + self.width / 2.0
+    }
+
+    // 参考设计中的纵坐标转换为屏幕纵坐标
+    pub fn ui_y(&self, base_y: f32) -> f32 {
+        self.top + base_y * self.scale
+    }
+
+    // 内容区域下边界
+    pub fn bottom(&self) -> f32 {
+        self.top + BASE_HEIGHT * self.scale
+    }
+
+    // 棋盘网格下边缘
+    pub fn board_bottom(&self) -> f32 {
+        self.board_y + self.board_extent
+    }
+
+    // 棋盘交叉点的屏幕坐标
+    pub fn intersection(&self, row: usize, col: usize) -> (f32, f32) {
+        (
+            self.board_x + col as f32 * self.cell_size,
+            self.board_y + row as f32 * self.cell_size,
+        )
+    }
+
+    // 鼠标坐标转换为棋盘格子坐标
+    pub fn mouse_to_grid(&self, x: f32, y: f32) -> Option<(usize, usize)> {
+        let grid_x = x - self.board_x;
+        let grid_y = y - self.board_y;
+        let tolerance = self.cell_size / 2.0;
+
+        if grid_x < -tolerance || grid_y < -tolerance ||
+           grid_x > self.board_extent + tolerance ||
+           grid_y > self.board_extent + tolerance {
+            return None;
+        }
+
+        let col = (grid_x / self.cell_size).round() as usize;
+        let row = (grid_y / self.cell_size).round() as usize;
+
+        if row < self.board_size && col < self.board_size {
+            Some((row, col))
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{Player, ProbPiece};
+
+// 经过一点的四个方向：横、竖、右下斜、左下斜
+const DIRECTIONS: [(i32, i32); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+// 一个连线窗口（长度为获胜子数）内的棋子统计：黑白双方按坍缩后的百分比累加（确定棋子计100）
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct Window {
+    pub black: u16,
+    pub white: u16,
+    pub stones: u8,
+}
+
+impl Window {
+    // 窗口内属于某一方的期望棋子数
+    pub fn expected(&self, player: Player) -> f32 {
+        let percent = match player {
+            Player::Black => self.black,
+            Player::White => self.white,
+        };
+        percent as f32 / 100.0
+    }
+}
+
+// 连线窗口统计表：落子或改变某点时只更新经过该点的窗口（每个方向至多 length 个）
+#[derive(Clone, Debug)]
+pub struct LineTable {
+    size: usize,
+    length: usize, // 窗口长度（获胜子数）
+    cells: Vec<ProbPiece>,
+    windows: Vec<Window>, // 下标为 (方向 * size + 起点行) * size + 起点列
+}
+
+impl LineTable {
+    pub fn new(size: usize, length: usize) -> Self {
+        LineTable {
+            size,
+            length,
+            cells: vec![ProbPiece::Empty; size * size],
+            windows: vec![Window::default(); DIRECTIONS.len() * size * size],
+        }
+    }
+
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    // 经过某点的全部窗口下标（窗口必须完整落在棋盘内）
+    fn window_indices(&self, row: usize, col: usize) -> impl Iterator<Item = usize> + '_ {
+        let size = self.size as i32;
+        let length = self.length as i32;
+        DIRECTIONS.iter().enumerate().flat_map(move |(direction, &(dr, dc))| {
+            (0..length).filter_map(move |offset| {
+                let (start_r, start_c) = (row as i32 - dr * offset, col as i32 - dc * offset);
+                let (end_r, end_c) = (start_r + dr * (length - 1), start_c + dc * (length - 1));
+                let inside = |r: i32, c: i32| r >= 0 && r < size && c >= 0 && c < size;
+                (inside(start_r, start_c) && inside(end_r, end_c))
+                    .then(|| ((direction as i32 * size + start_r) * size + start_c) as usize)
+            })
+        })
+    }
+
+    // 设置某点的棋子（可为空），同步更新经过该点的窗口
+    pub fn set(&mut self, row: usize, col: usize, piece: ProbPiece) {
+        let cell = row * self.size + col;
+        let old = std::mem::replace(&mut self.cells[cell], piece);
+        if old == piece {
+            return;
+        }
+        let indices: Vec<usize> = self.window_indices(row, col).collect();
+        for index in indices {
+            let window = &mut self.windows[index];
+            if old != ProbPiece::Empty {
+                let (black, white) = old.odds();
+                window.black -= black as u16;
+                window.white -= white as u16;
+                window.stones -= 1;
+            }
+            if piece != ProbPiece::Empty {
+                let (black, white) = piece.odds();
+                window.black += black as u16;
+                window.white += white as u16;
+                window.stones += 1;
+            }
+        }
+    }
+
+    // 经过某点的全部窗口统计
+    pub fn windows_through(&self, row: usize, col: usize) -> impl Iterator<Item = Window> + '_ {
+        self.window_indices(row, col).map(|index| self.windows[index])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::rand::rngs::StdRng;
+    use ::rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    // 直接数出经过某点的全部窗口（与增量维护无关的朴素计算）
+    fn brute_windows(board: &[Vec<ProbPiece>], length: usize, row: usize, col: usize) -> Vec<Window> {
+        let size = board.len() as i32;
+        let mut windows = Vec::new();
+        for (dr, dc) in DIRECTIONS {
+            for offset in 0..length as i32 {
+                let start = (row as i32 - dr * offset, col as i32 - dc * offset);
+                let cells: Vec<(i32, i32)> =
+                    (0..length as i32).map(|step| (start.0 + dr * step, start.1 + dc * step)).collect();
+                if cells.iter().any(|&(r, c)| r < 0 || r >= size || c < 0 || c >= size) {
+                    continue;
+                }
+                let mut window = Window::default();
+                for (r, c) in cells {
+                    let piece = board[r as usize][c as usize];
+                    if piece != ProbPiece::Empty {
+                        let (black, white) = piece.odds();
+                        window.black += black as u16;
+                        window.white += white as u16;
+                        window.stones += 1;
+                    }
+                }
+                windows.push(window);
+            }
+        }
+        windows
+    }
+
+    #[test]
+    fn corner_windows() {
+        let mut table = LineTable::new(7, 5);
+        table.set(0, 0, ProbPiece::BLACK_90);
+        // 角上只有横、竖、右下斜各一个窗口
+        let windows: Vec<Window> = table.windows_through(0, 0).collect();
+        assert_eq!(windows, vec![Window { black: 90, white: 10, stones: 1 }; 3]);
+        assert_eq!(table.windows_through(3, 3).count(), 3 * 4);
+    }
+
+    #[test]
+    fn incremental_set_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(39);
+        let pieces = [
+            ProbPiece::Empty,
+            ProbPiece::BLACK_90,
+            ProbPiece::BLACK_30,
+            ProbPiece::Stone(55),
+            ProbPiece::Superposed(70),
+        ];
+        for (size, length) in [(5, 5), (7, 4), (9, 5), (9, 6)] {
+            let mut board = vec![vec![ProbPiece::Empty; size]; size];
+            let mut table = LineTable::new(size, length);
+            // 落子、改变与清空交替进行
+            for _ in 0..300 {
+                let (row, col) = (rng.gen_range(0..size), rng.gen_range(0..size));
+                let piece = pieces[rng.gen_range(0..pieces.len())];
+                board[row][col] = piece;
+                table.set(row, col, piece);
+            }
+            for row in 0..size {
+                for col in 0..size {
+                    let windows: Vec<Window> = table.windows_through(row, col).collect();
+                    assert_eq!(windows, brute_windows(&board, length, row, col), "({}, {})", row, col);
+                }
+            }
+        }
+    }
+}
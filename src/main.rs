@@ -1,17 +1,92 @@
+mod ai;
+mod anim;
+mod audio;
+mod bench;
+mod bitboard;
+mod i18n;
+mod layout;
+mod lines;
+mod menu;
+mod record;
+mod renju;
+mod replay;
+mod settings;
+mod text;
+mod theme;
+mod tooltip;
+mod ui;
+
 use macroquad::prelude::*;
 use ::rand::Rng;
 use ::rand::thread_rng;
-
-// 概率棋子类型：表示棋子为黑棋的概率
+use std::iter::Peekable;
+use std::slice::Iter;
+use anim::CollapseAnimation;
+use audio::{SoundEffect, Sounds};
+use i18n::{fill, Strings};
+use layout::Layout;
+use lines::LineTable;
+use record::{CardRecord, GameRecord, MoveRecord};
+use replay::Replay;
+use settings::Settings;
+use text::{draw_ui_text, measure_ui_text, wrap_ui_text};
+use theme::Theme;
+use ui::{draw_buttons, find_button, navigate_focus, triggered_action, Button, ButtonStyle, UiAction};
+
+// 概率棋子类型：表示棋子为黑棋的概率（百分比，概率漂移时可取0到100间任意值）
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum ProbPiece {
-    Black90,
-    Black70,
-    Black30,
-    Black10,
+    Stone(u8),      // 黑棋百分比
+    Superposed(u8), // 同时落在两个交叉点的棋子，坍缩后只出现在其中一点（黑棋百分比）
     Empty,
 }
 
+impl ProbPiece {
+    // 落子时使用的四档概率棋子
+    const BLACK_90: ProbPiece = ProbPiece::Stone(90);
+    const BLACK_70: ProbPiece = ProbPiece::Stone(70);
+    const BLACK_30: ProbPiece = ProbPiece::Stone(30);
+    const BLACK_10: ProbPiece = ProbPiece::Stone(10);
+
+    // 棋子为黑棋的百分比（空位为0）
+    fn black_percent(self) -> u8 {
+        match self {
+            ProbPiece::Stone(percent) | ProbPiece::Superposed(percent) => percent,
+            ProbPiece::Empty => 0,
+        }
+    }
+
+    // 坍缩后该点为黑棋、白棋的百分比：叠加棋子只有一半机会出现在该点
+    fn odds(self) -> (u8, u8) {
+        match self {
+            ProbPiece::Stone(percent) => (percent, 100 - percent),
+            ProbPiece::Superposed(percent) => (percent / 2, (100 - percent) / 2),
+            ProbPiece::Empty => (0, 0),
+        }
+    }
+
+    // 由黑棋百分比还原概率棋子
+    fn from_black_percent(percent: u8) -> Option<ProbPiece> {
+        (percent <= 100).then_some(ProbPiece::Stone(percent))
+    }
+
+    // 向目标百分比漂移至多 step 个百分点（不越过目标）
+    fn drifted(self, target: u8, step: u8) -> ProbPiece {
+        let drift = |percent: u8| {
+            if percent < target {
+                percent.saturating_add(step).min(target)
+            } else {
+                percent.saturating_sub(step).max(target)
+            }
+        };
+        match self {
+            ProbPiece::Stone(percent) => ProbPiece::Stone(drift(percent)),
+            ProbPiece::Superposed(percent) => ProbPiece::Superposed(drift(percent)),
+            ProbPiece::Empty => ProbPiece::Empty,
+        }
+    }
+}
+
 // 确定棋子类型：实际落子后的明确颜色
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum DefinitePiece {
@@ -27,87 +102,662 @@ enum Player {
     White,
 }
 
-// 获胜棋子记录：存储黑白双方获胜的棋子位置
+impl Player {
+    // 对方玩家
+    fn opponent(self) -> Player {
+        match self {
+            Player::Black => Player::White,
+            Player::White => Player::Black,
+        }
+    }
+}
+
+// 获胜棋子记录：存储黑白双方的每一条获胜连线（长连记录完整长度，按连线方向排列）
 #[derive(Clone, Debug, PartialEq, Eq, Default)]
 struct WinningPieces {
-    black: Vec<(usize, usize)>,
-    white: Vec<(usize, usize)>,
+    black: Vec<Vec<(usize, usize)>>,
+    white: Vec<Vec<(usize, usize)>>,
+}
+
+impl WinningPieces {
+    // 某一方的获胜连线
+    fn lines(&self, player: Player) -> &[Vec<(usize, usize)>] {
+        match player {
+            Player::Black => &self.black,
+            Player::White => &self.white,
+        }
+    }
+}
+
+// 对局结果
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Outcome {
+    Win(Player),     // 一方五子连珠
+    BothWin,         // 双方同时成五
+    BoardFull,       // 棋盘已满
+    Timeout(Player), // 该玩家超时判负
+}
+
+impl Outcome {
+    // 对局记录中使用的结果代码
+    fn key(self) -> &'static str {
+        match self {
+            Outcome::Win(Player::Black) => "black",
+            Outcome::Win(Player::White) => "white",
+            Outcome::BothWin => "both",
+            Outcome::BoardFull => "full",
+            Outcome::Timeout(Player::Black) => "timeout-black",
+            Outcome::Timeout(Player::White) => "timeout-white",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Outcome> {
+        match key {
+            "black" => Some(Outcome::Win(Player::Black)),
+            "white" => Some(Outcome::Win(Player::White)),
+            "both" => Some(Outcome::BothWin),
+            "full" => Some(Outcome::BoardFull),
+            "timeout-black" => Some(Outcome::Timeout(Player::Black)),
+            "timeout-white" => Some(Outcome::Timeout(Player::White)),
+            _ => None,
+        }
+    }
+
+    // 结果的显示文本
+    fn text(self, strings: &Strings) -> &'static str {
+        match self {
+            Outcome::Win(Player::Black) => strings.black_wins,
+            Outcome::Win(Player::White) => strings.white_wins,
+            Outcome::BothWin => strings.both_win,
+            Outcome::BoardFull => strings.board_full,
+            Outcome::Timeout(Player::Black) => strings.white_wins_on_time,
+            Outcome::Timeout(Player::White) => strings.black_wins_on_time,
+        }
+    }
+}
+
+// 对手类型：双人同屏或由电脑执某一方
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Opponent {
+    Human,
+    ComputerWhite,
+    ComputerBlack,
+}
+
+impl Opponent {
+    // 由电脑操作的一方
+    fn computer_player(self) -> Option<Player> {
+        match self {
+            Opponent::Human => None,
+            Opponent::ComputerWhite => Some(Player::White),
+            Opponent::ComputerBlack => Some(Player::Black),
+        }
+    }
+}
+
+// 概率档位：每方可落的棋子概率
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ProbTiers {
+    Rotate,     // 90%/70% 每回合轮换
+    StrongOnly, // 只落90%棋子
+    WeakOnly,   // 只落70%棋子
+}
+
+// 长连规则：超过获胜子数的长连是否算作获胜
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum WinRule {
+    Freestyle,  // 双方长连均获胜
+    Exact,      // 双方只有恰好连成获胜子数才获胜
+    BlackExact, // 黑方须恰好连成，白方长连也获胜
+    WhiteExact, // 白方须恰好连成，黑方长连也获胜
+}
+
+impl WinRule {
+    // 该方是否须恰好连成获胜子数
+    fn exact_length(self, player: Player) -> bool {
+        match self {
+            WinRule::Freestyle => false,
+            WinRule::Exact => true,
+            WinRule::BlackExact => player == Player::Black,
+            WinRule::WhiteExact => player == Player::White,
+        }
+    }
+}
+
+// 获胜条件：获胜子数与长连规则
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct WinCondition {
+    length: usize, // 连成多少子获胜
+    rule: WinRule, // 长连规则
+}
+
+impl WinCondition {
+    // 某一方长度为 run 的连线是否获胜
+    fn wins(self, player: Player, run: usize) -> bool {
+        if self.rule.exact_length(player) { run == self.length } else { run >= self.length }
+    }
+}
+
+// 每回合落子数
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum StonesPerTurn {
+    Fixed(u8), // 每回合固定落子数
+    Connect6,  // 六子棋式：首回合一子，之后每回合两子
+}
+
+impl StonesPerTurn {
+    // 第 turn 回合（从0开始）应落的子数
+    fn count(self, turn: usize) -> u8 {
+        match self {
+            StonesPerTurn::Fixed(count) => count,
+            StonesPerTurn::Connect6 if turn == 0 => 1,
+            StonesPerTurn::Connect6 => 2,
+        }
+    }
+}
+
+// 开局规则：用交换平衡先手优势
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum OpeningRule {
+    Standard, // 不交换，黑方直接先行
+    Swap,     // 先手方摆三子（黑白黑），后手方选择执黑或执白
+    Swap2,    // 同上，后手方也可再摆两子（白黑），改由先手方选择颜色
+    ProbSwap, // 同 Swap，但开局三子一律为70%棋子，削弱概率上的先手优势
+}
+
+// 开局阶段：交换规则下先摆开局棋子、再选择颜色
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum OpeningStage {
+    Placing,       // 先手方摆开局棋子
+    SecondChooses, // 后手方选择颜色（Swap2 也可再摆两子）
+    Extending,     // 后手方再摆两子
+    FirstChooses,  // 先手方选择颜色
+    Done,          // 开局结束，正常对局
+}
+
+// 开局中的选择
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum OpeningChoice {
+    Black,  // 执黑
+    White,  // 执白
+    Extend, // 再摆两子
+}
+
+impl OpeningChoice {
+    // 对局记录中使用的代码
+    fn key(self) -> &'static str {
+        match self {
+            OpeningChoice::Black => "black",
+            OpeningChoice::White => "white",
+            OpeningChoice::Extend => "extend",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<OpeningChoice> {
+        match key {
+            "black" => Some(OpeningChoice::Black),
+            "white" => Some(OpeningChoice::White),
+            "extend" => Some(OpeningChoice::Extend),
+            _ => None,
+        }
+    }
+}
+
+// 预览规则：预览次数如何发放
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PreviewRule {
+    PerTurn,        // 每回合重置为设定次数
+    PerGame,        // 每方整局共有设定次数
+    Bank,           // 每回合存入设定次数，未用完的累积到下回合
+    CostsPlacement, // 每回合设定次数，但预览后本回合不能再落子
+}
+
+// 坍缩后双方同时成五时的判定
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DoubleWinRule {
+    Draw,          // 平局
+    ObserverLoses, // 预览的一方判负
+    ObserverWins,  // 预览的一方获胜
+    CountLines,    // 获胜连线多的一方获胜，相同则平局
+    Reroll,        // 重新坍缩，直到只有一方成五
+}
+
+// 主用时之外的加时方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TimeBonus {
+    None,
+    Fischer(u32),     // 费舍尔加秒：每走完一回合加若干秒
+    ByoYomi(u8, u32), // 读秒：主用时用完后每回合限时若干秒，超时一次用掉一次读秒（次数、秒数）
+}
+
+// 概率漂移：每回合结束时已有棋子的黑棋概率向目标漂移若干个百分点
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Drift {
+    Off,
+    Even(u8),  // 向50%漂移，棋子越来越难以预料
+    Owner(u8), // 向落子方的颜色漂移，棋子逐渐确定
+}
+
+// 纠缠落子：一次落下两枚棋子，坍缩时颜色相反或相同
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum EntangleRule {
+    Off,
+    Opposite, // 两枚棋子坍缩为相反颜色
+    Same,     // 两枚棋子坍缩为相同颜色
+}
+
+// 一对纠缠棋子（关联组）：坍缩时第二枚的颜色由第一枚决定
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Entanglement {
+    move_index: usize,      // 第一枚棋子在落子记录中的下标，第二枚紧随其后
+    first: (usize, usize),
+    second: (usize, usize),
+    same: bool,             // 坍缩为相同颜色（否则相反）
+}
+
+impl Entanglement {
+    // 第二枚棋子的概率：与第一枚相同或互补
+    fn second_piece(self, first: ProbPiece) -> ProbPiece {
+        match first {
+            ProbPiece::Stone(percent) if !self.same => ProbPiece::Stone(100 - percent),
+            piece => piece,
+        }
+    }
+
+    // 由第一枚坍缩后的颜色得出第二枚的颜色
+    fn second_colour(self, first: DefinitePiece) -> DefinitePiece {
+        match first {
+            DefinitePiece::Black if !self.same => DefinitePiece::White,
+            DefinitePiece::White if !self.same => DefinitePiece::Black,
+            piece => piece,
+        }
+    }
+}
+
+// 叠加落子：一枚棋子同时落在两个交叉点，坍缩时先决定出现在哪一点，再决定颜色
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Superposition {
+    move_index: usize,           // 第一个点在落子记录中的下标，第二个点紧随其后
+    points: [(usize, usize); 2],
+}
+
+// 坍缩时需要一并处理的关联组：纠缠对与叠加棋子
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Correlations {
+    entanglements: Vec<Entanglement>,
+    superpositions: Vec<Superposition>,
+}
+
+// 一手落在两个交叉点的落子方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PairMove {
+    Entangle,  // 纠缠落子
+    Superpose, // 叠加落子
+}
+
+// 能力卡：每方每种有限张数，回合中代替不了落子，每回合至多使用一张
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PowerCard {
+    Remove,   // 移除对方一枚未观测的棋子
+    Flip,     // 翻转一枚未观测棋子的概率
+    Collapse, // 单独坍缩一枚未观测的棋子
+}
+
+impl PowerCard {
+    const ALL: [PowerCard; 3] = [PowerCard::Remove, PowerCard::Flip, PowerCard::Collapse];
+
+    // 对局记录中使用的代码
+    fn key(self) -> &'static str {
+        match self {
+            PowerCard::Remove => "remove",
+            PowerCard::Flip => "flip",
+            PowerCard::Collapse => "collapse",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<PowerCard> {
+        PowerCard::ALL.into_iter().find(|card| card.key() == key)
+    }
+}
+
+// 对局设置：在新游戏界面中选择
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct GameConfig {
+    opponent: Opponent,     // 对手类型
+    board_size: usize,      // 棋盘路数
+    tiers: ProbTiers,       // 概率档位
+    previews: u8,           // 预览次数（按预览规则发放）
+    preview_rule: PreviewRule, // 预览规则
+    double_win: DoubleWinRule, // 双方同时成五时的判定
+    drift: Drift,           // 概率漂移
+    entangle: EntangleRule, // 纠缠落子
+    superposition: bool,    // 允许叠加落子
+    power_cards: u8,        // 每方每种能力卡的张数，0为关闭
+    time_minutes: u32,      // 每方用时（分钟），0为不限时（读秒时可只用读秒）
+    time_bonus: TimeBonus,  // 加时方式
+    win_rule: WinRule,      // 长连规则
+    win_length: usize,      // 连成多少子获胜
+    stones_per_turn: StonesPerTurn, // 每回合落子数
+    renju: bool,            // 连珠禁手：黑方不得下三三、四四、长连（仅五子获胜时有效）
+    opening: OpeningRule,   // 开局规则
+}
+
+impl GameConfig {
+    // 是否计时：有主用时或读秒
+    fn timed(self) -> bool {
+        self.time_minutes > 0 || matches!(self.time_bonus, TimeBonus::ByoYomi(..))
+    }
+
+    // 连珠禁手是否生效：禁手棋形按五子定义
+    fn renju_active(self) -> bool {
+        self.renju && self.win_length == 5
+    }
+
+    // 实际使用的获胜条件：连珠禁手下黑方长连不算获胜
+    fn win_condition(self) -> WinCondition {
+        let rule = match (self.renju_active(), self.win_rule) {
+            (true, WinRule::Freestyle) => WinRule::BlackExact,
+            (true, WinRule::WhiteExact) => WinRule::Exact,
+            (_, rule) => rule,
+        };
+        WinCondition { length: self.win_length, rule }
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            opponent: Opponent::Human,
+            board_size: 15,
+            tiers: ProbTiers::Rotate,
+            previews: 1,
+            preview_rule: PreviewRule::PerTurn,
+            double_win: DoubleWinRule::Draw,
+            drift: Drift::Off,
+            entangle: EntangleRule::Off,
+            superposition: false,
+            power_cards: 0,
+            time_minutes: 0,
+            time_bonus: TimeBonus::None,
+            win_rule: WinRule::Freestyle,
+            win_length: 5,
+            stones_per_turn: StonesPerTurn::Fixed(1),
+            renju: false,
+            opening: OpeningRule::Standard,
+        }
+    }
 }
 
 // 游戏常量配置
-const BOARD_SIZE: usize = 15;
-const SCALE: f32 = 1.5;
-const WINDOW_WIDTH: f32 = 1200.0;
-const WINDOW_HEIGHT: f32 = 1200.0;
-const BASE_CELL_SIZE: f32 = 30.0;
-const CELL_SIZE: f32 = BASE_CELL_SIZE * SCALE;
-const PIECE_RADIUS: f32 = CELL_SIZE / 2.0;
-const BOARD_ACTUAL_WIDTH: f32 = CELL_SIZE * (BOARD_SIZE - 1) as f32;
-const BOARD_ACTUAL_HEIGHT: f32 = CELL_SIZE * (BOARD_SIZE - 1) as f32;
-const BOARD_OFFSET_X: f32 = (WINDOW_WIDTH - BOARD_ACTUAL_WIDTH) / 2.0;
-const BOARD_OFFSET_Y: f32 = (WINDOW_HEIGHT - BOARD_ACTUAL_HEIGHT) / 2.0 + 100.0;
 const END_TURN_BUTTON_WIDTH: f32 = 160.0;
 const END_TURN_BUTTON_HEIGHT: f32 = 50.0;
 const GAME_OVER_BUTTON_SCALE: f32 = 1.5;
-const HOVER_SCALE: f32 = 1.05;
-const PREVIEW_ALPHA: f32 = 0.4;
+const COMPUTER_MOVE_DELAY: f32 = 0.6;
+const COLLAPSE_SPIN_RATE: f32 = 14.0;
+const OPENING_STONES: usize = 3; // 交换开局先手方摆的棋子数
+const SWAP2_STONES: usize = 5;   // Swap2 后手方再摆两子后的棋子数
+const PREVIEW_BANK_LIMIT: u8 = 9; // 累积预览规则下每方最多存下的预览次数
+const REROLL_LIMIT: usize = 100;  // 双方成五重新坍缩的最多次数，仍未分出胜负则判平局
+
+// 界面：主菜单、新游戏设置、对局、复盘
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Screen {
+    MainMenu,
+    NewGame,
+    Settings,
+    Playing,
+    Replay,
+}
+
+// 局面标识：棋盘只在落子、使用能力卡和回合结束（概率漂移）时变化
+type PositionKey = (usize, usize, usize);
 
 // 游戏状态结构体：存储游戏所有核心数据
 struct GameState {
+    config: GameConfig,                       // 对局设置
     board: Vec<Vec<ProbPiece>>,               // 游戏棋盘（概率棋子）
     show_observation: bool,                   // 是否显示预览棋盘
     observation_board: Vec<Vec<DefinitePiece>>,// 预览棋盘（确定棋子）
-    observation_winner: Option<&'static str>, // 预览结果的获胜者
-    observe_remaining: u8,                    // 剩余预览次数
+    observation_winner: Option<Outcome>,      // 预览结果（对局结果）
+    observe_remaining: [u8; 2],               // 黑白双方剩余预览次数
+    previews_used: Vec<usize>,                // 每次预览所在的回合
+    placement_forfeited: bool,                // 本回合已用预览代替落子
     current_player: Player,                   // 当前回合玩家
     black_prob_index: usize,                  // 黑方概率棋子切换索引
     white_prob_index: usize,                  // 白方概率棋子切换索引
     winning_pieces: WinningPieces,            // 获胜棋子记录
     game_over: bool,                          // 游戏是否结束
-    current_turn_move_count: u8,              // 本回合落子数
-    show_prob_hint: bool,                     // 是否显示概率提示文本
+    current_turn_move_count: u8,              // 本回合已落子数
+    turn: usize,                              // 已结束的回合数
+    turn_start: usize,                        // 本回合开始时的落子记录数（两点落子一手有两条记录）
+    show_prob_hint: bool,                     // 本回合是否仍显示下一手提示（落子后隐藏）
+    end_turn_pending: bool,                   // 已按下结束回合、等待再次确认
+    restart_pending: bool,                    // 对局中已按下 R、等待再按一次确认重新开始
+    cursor: (usize, usize),                   // 键盘光标所在交叉点
+    cursor_visible: bool,                     // 是否显示键盘光标
+    moves: Vec<MoveRecord>,                   // 落子记录
+    clocks: [f32; 2],                         // 黑白双方剩余主用时（秒）
+    periods: [u8; 2],                         // 黑白双方剩余读秒次数
+    period_clock: f32,                        // 当前回合读秒剩余时间（秒）
+    paused: bool,                             // 对局是否暂停（时钟停走、棋盘隐藏）
+    correlations: Correlations,               // 已落下的纠缠棋子对与叠加棋子
+    pair_move: Option<PairMove>,              // 当前玩家选择的两点落子方式
+    pending_pair: Option<(usize, usize)>,     // 两点落子已选的第一个交叉点
+    retired: Correlations,                    // 已被能力卡移除或坍缩的关联组（只用于导出记录）
+    cards: [[u8; 3]; 2],                      // 黑白双方各种能力卡的剩余张数
+    card_used: bool,                          // 本回合已使用能力卡
+    active_card: Option<PowerCard>,           // 已选中、等待选择目标棋子的能力卡
+    cards_played: Vec<CardRecord>,            // 能力卡使用记录
+    observed: Vec<(usize, usize)>,            // 被能力卡坍缩、颜色已确定的棋子
+    forbidden: Option<(PositionKey, Vec<(usize, usize)>)>, // 当前局面下黑方的禁手点（按局面缓存）
+    computer_wait: f32,                       // 电脑落子前的等待时间
+    collapse: Option<CollapseAnimation>,      // 正在播放的预览坍缩动画
+    lines: LineTable,                         // 连线窗口统计（落子时增量更新，供电脑评估）
+    opening: OpeningStage,                    // 开局阶段
+    swapped: bool,                            // 交换开局后先手方改执白
+    opening_choices: Vec<OpeningChoice>,      // 开局中已作出的选择
 }
 
-// GameState默认实现：初始化游戏状态
-impl Default for GameState {
-    fn default() -> Self {
-        let board = vec![vec![ProbPiece::Empty; BOARD_SIZE]; BOARD_SIZE];
-        GameState {
-            board,
+impl GameState {
+    // 按对局设置初始化游戏状态
+    fn new(config: GameConfig) -> Self {
+        let size = config.board_size;
+        let time = config.time_minutes as f32 * 60.0;
+        let (periods, period_seconds) = match config.time_bonus {
+            TimeBonus::ByoYomi(periods, seconds) => (periods, seconds as f32),
+            _ => (0, 0.0),
+        };
+        let mut state = GameState {
+            config,
+            board: vec![vec![ProbPiece::Empty; size]; size],
             show_observation: false,
-            observation_board: vec![vec![DefinitePiece::Empty; BOARD_SIZE]; BOARD_SIZE],
+            observation_board: vec![vec![DefinitePiece::Empty; size]; size],
             observation_winner: None,
-            observe_remaining: 1,
+            // 整局规则一开始发放全部次数，其余规则在各自回合开始时发放
+            observe_remaining: [if config.preview_rule == PreviewRule::PerGame { config.previews } else { 0 }; 2],
+            previews_used: Vec::new(),
+            placement_forfeited: false,
             current_player: Player::Black,
             black_prob_index: 0,
             white_prob_index: 0,
             winning_pieces: WinningPieces::default(),
             game_over: false,
             current_turn_move_count: 0,
+            turn: 0,
+            turn_start: 0,
             show_prob_hint: true,
+            end_turn_pending: false,
+            restart_pending: false,
+            cursor: (size / 2, size / 2),
+            cursor_visible: false,
+            moves: Vec::new(),
+            clocks: [time, time],
+            periods: [periods; 2],
+            period_clock: period_seconds,
+            paused: false,
+            correlations: Correlations::default(),
+            pair_move: None,
+            pending_pair: None,
+            retired: Correlations::default(),
+            cards: [[config.power_cards; 3]; 2],
+            card_used: false,
+            active_card: None,
+            cards_played: Vec::new(),
+            observed: Vec::new(),
+            forbidden: None,
+            computer_wait: COMPUTER_MOVE_DELAY,
+            collapse: None,
+            lines: LineTable::new(size, config.win_length),
+            opening: if config.opening == OpeningRule::Standard {
+                OpeningStage::Done
+            } else {
+                OpeningStage::Placing
+            },
+            swapped: false,
+            opening_choices: Vec::new(),
+        };
+        if state.opening == OpeningStage::Done {
+            grant_previews(&mut state);
         }
+        state
     }
-}
 
-// 鼠标坐标转换为棋盘格子坐标
-fn mouse_to_grid(x: f32, y: f32) -> Option<(usize, usize)> {
-    let grid_x = x - BOARD_OFFSET_X;
-    let grid_y = y - BOARD_OFFSET_Y;
-    let tolerance = 15.0 * SCALE;
-    
-    if grid_x < -tolerance || grid_y < -tolerance || 
-       grid_x > CELL_SIZE * BOARD_SIZE as f32 + tolerance || 
-       grid_y > CELL_SIZE * BOARD_SIZE as f32 + tolerance {
-        return None;
+    // 由对局记录恢复：依次落子并结束回合
+    fn from_record(record: &GameRecord) -> Self {
+        let mut state = GameState::new(record.config);
+        let mut choices = record.opening_choices.iter();
+        let mut previews = record.previews.iter().peekable();
+        let mut cards = record.cards.iter().peekable();
+        let mut index = 0;
+        while index < record.moves.len() {
+            replay_turn_actions(&mut state, &mut previews, &mut cards);
+            // 纠缠落子与叠加落子的两个点一起落下，只算一手
+            let pair = record.entanglements.iter().find(|&&(first, _)| first == index);
+            let superposed = record.superpositions.contains(&index);
+            let count = if pair.is_some() || superposed { 2 } else { 1 };
+            let Some(stones) = record.moves.get(index..index + count) else {
+                break;
+            };
+            if let (Some(&(_, same)), [first, second]) = (pair, stones) {
+                state.correlations.entanglements.push(Entanglement {
+                    move_index: index,
+                    first: (first.row, first.col),
+                    second: (second.row, second.col),
+                    same,
+                });
+            }
+            if let (true, [first, second]) = (superposed, stones) {
+                state.correlations.superpositions.push(Superposition {
+                    move_index: index,
+                    points: [(first.row, first.col), (second.row, second.col)],
+                });
+            }
+            let player = stones[0].player;
+            state.current_player = player;
+            for mv in stones {
+                let piece = if superposed { ProbPiece::Superposed(mv.piece.black_percent()) } else { mv.piece };
+                state.board[mv.row][mv.col] = piece;
+                state.lines.set(mv.row, mv.col, piece);
+                state.moves.push(MoveRecord { piece, ..*mv });
+            }
+            state.current_turn_move_count += 1;
+            index += count;
+            // 本回合已落满，或下一手换了落子方，则结束回合
+            let next_player = record.moves.get(index).map(|next| next.player);
+            if state.turn_complete() || next_player.is_some_and(|next| next != player) {
+                end_turn(&mut state);
+                if state.choosing_colour() {
+                    if let Some(&choice) = choices.next() {
+                        apply_opening_choice(&mut state, choice);
+                    }
+                }
+            }
+        }
+        replay_turn_actions(&mut state, &mut previews, &mut cards);
+        if let Some(clocks) = record.clocks {
+            state.clocks = clocks;
+        }
+        if let Some(periods) = record.periods {
+            state.periods = periods;
+        }
+        if let Some(seconds) = record.period_clock {
+            state.period_clock = seconds;
+        }
+        state
     }
-    
-    let col = (grid_x / CELL_SIZE).round() as usize;
-    let row = (grid_y / CELL_SIZE).round() as usize;
-    
-    if row < BOARD_SIZE && col < BOARD_SIZE {
-        Some((row, col))
-    } else {
-        None
+
+    // 导出对局记录
+    fn to_record(&self) -> GameRecord {
+        GameRecord {
+            config: self.config,
+            moves: self.moves.clone(),
+            opening_choices: self.opening_choices.clone(),
+            previews: self.previews_used.clone(),
+            entanglements: self.all_correlations().entanglements.iter().map(|pair| (pair.move_index, pair.same)).collect(),
+            superpositions: self.all_correlations().superpositions.iter().map(|stone| stone.move_index).collect(),
+            cards: self.cards_played.clone(),
+            clocks: self.config.timed().then_some(self.clocks),
+            periods: matches!(self.config.time_bonus, TimeBonus::ByoYomi(..)).then_some(self.periods),
+            period_clock: matches!(self.config.time_bonus, TimeBonus::ByoYomi(..)).then_some(self.period_clock),
+            result: if self.game_over { self.observation_winner } else { None },
+        }
+    }
+
+    // 整局落下的全部关联组（含已被能力卡解除的），按落子顺序排列
+    fn all_correlations(&self) -> Correlations {
+        let mut all = self.correlations.clone();
+        all.entanglements.extend_from_slice(&self.retired.entanglements);
+        all.superpositions.extend_from_slice(&self.retired.superpositions);
+        all.entanglements.sort_by_key(|pair| pair.move_index);
+        all.superpositions.sort_by_key(|stone| stone.move_index);
+        all
+    }
+
+    // 当前需要操作的一方，按开局时的座位计：先手方为 Black，后手方为 White
+    fn acting_seat(&self) -> Player {
+        match self.opening {
+            OpeningStage::Placing | OpeningStage::FirstChooses => Player::Black,
+            OpeningStage::SecondChooses | OpeningStage::Extending => Player::White,
+            OpeningStage::Done if self.swapped => self.current_player.opponent(),
+            OpeningStage::Done => self.current_player,
+        }
+    }
+
+    // 本回合应落的子数：交换开局摆子时每回合一子
+    fn stones_this_turn(&self) -> u8 {
+        if self.opening == OpeningStage::Done {
+            self.config.stones_per_turn.count(self.turn)
+        } else {
+            1
+        }
+    }
+
+    // 本回合是否已落满（棋盘已满或已用预览代替落子时也视为落满）
+    fn turn_complete(&self) -> bool {
+        self.current_turn_move_count >= self.stones_this_turn() ||
+            self.placement_forfeited ||
+            self.board.iter().all(|line| line.iter().all(|&piece| piece != ProbPiece::Empty))
+    }
+
+    // 当前玩家剩余预览次数
+    fn previews_left(&self) -> u8 {
+        self.observe_remaining[self.current_player as usize]
+    }
+
+    // 当前能否预览：开局结束后有剩余次数；预览代替落子时须在本回合落子之前
+    fn can_preview(&self) -> bool {
+        let placed = self.config.preview_rule == PreviewRule::CostsPlacement && self.current_turn_move_count > 0;
+        !self.game_over && self.opening == OpeningStage::Done && self.previews_left() > 0 && !placed
+    }
+
+    // 是否正在等待开局中的颜色选择
+    fn choosing_colour(&self) -> bool {
+        matches!(self.opening, OpeningStage::SecondChooses | OpeningStage::FirstChooses)
+    }
+
+    // 当前回合是否由电脑操作（电脑所在座位见 Opponent::computer_player）
+    fn is_computer_turn(&self) -> bool {
+        self.config.opponent.computer_player() == Some(self.acting_seat())
     }
 }
 
@@ -115,26 +765,35 @@ fn mouse_to_grid(x: f32, y: f32) -> Option<(usize, usize)> {
 fn prob_to_definite(piece: ProbPiece) -> DefinitePiece {
     let mut rng = thread_rng();
     match piece {
-        ProbPiece::Black90 => if rng.gen_range(0..100) < 90 { DefinitePiece::Black } else { DefinitePiece::White },
-        ProbPiece::Black70 => if rng.gen_range(0..100) < 70 { DefinitePiece::Black } else { DefinitePiece::White },
-        ProbPiece::Black30 => if rng.gen_range(0..100) < 30 { DefinitePiece::Black } else { DefinitePiece::White },
-        ProbPiece::Black10 => if rng.gen_range(0..100) < 10 { DefinitePiece::Black } else { DefinitePiece::White },
+        ProbPiece::Stone(percent) | ProbPiece::Superposed(percent) => {
+            if rng.gen_range(0..100) < percent { DefinitePiece::Black } else { DefinitePiece::White }
+        }
         ProbPiece::Empty => DefinitePiece::Empty,
     }
 }
 
 // 获取当前玩家要落的概率棋子
 fn get_current_prob_piece(state: &GameState) -> ProbPiece {
+    let prob_swap_opening = state.config.opening == OpeningRule::ProbSwap && state.opening == OpeningStage::Placing;
+    let index = match state.config.tiers {
+        _ if prob_swap_opening => 1,
+        ProbTiers::Rotate => match state.current_player {
+            Player::Black => state.black_prob_index,
+            Player::White => state.white_prob_index,
+        },
+        ProbTiers::StrongOnly => 0,
+        ProbTiers::WeakOnly => 1,
+    };
     match state.current_player {
-        Player::Black => match state.black_prob_index {
-            0 => ProbPiece::Black90,
-            1 => ProbPiece::Black70,
-            _ => ProbPiece::Black90,
+        Player::Black => match index {
+            0 => ProbPiece::BLACK_90,
+            1 => ProbPiece::BLACK_70,
+            _ => ProbPiece::BLACK_90,
         },
-        Player::White => match state.white_prob_index {
-            0 => ProbPiece::Black10,
-            1 => ProbPiece::Black30,
-            _ => ProbPiece::Black10,
+        Player::White => match index {
+            0 => ProbPiece::BLACK_10,
+            1 => ProbPiece::BLACK_30,
+            _ => ProbPiece::BLACK_10,
         },
     }
 }
@@ -147,64 +806,655 @@ fn switch_player_prob(state: &mut GameState) {
     }
 }
 
-// 检查棋盘获胜者：返回获胜者和获胜棋子位置
-fn check_winner(board: &[Vec<DefinitePiece>]) -> (Option<&'static str>, WinningPieces) {
+// 在指定位置落下当前玩家的概率棋子：返回是否落子成功
+fn place_piece(state: &mut GameState, row: usize, col: usize) -> bool {
+    if state.game_over || state.show_observation || state.turn_complete() || state.choosing_colour() {
+        return false;
+    }
+    if state.board[row][col] != ProbPiece::Empty {
+        return false;
+    }
+    if state.config.renju_active() && state.current_player == Player::Black && renju::is_forbidden(&state.board, row, col) {
+        return false;
+    }
+
+    let piece = get_current_prob_piece(state);
+    state.board[row][col] = piece;
+    state.lines.set(row, col, piece);
+    state.moves.push(MoveRecord { player: state.current_player, row, col, piece });
+    state.current_turn_move_count += 1;
+    state.show_prob_hint = !state.turn_complete();
+    true
+}
+
+// 一手落在两个交叉点前的检查：两点不同、都是空位且（连珠禁手时）都不是黑方禁手点
+fn can_place_pair(state: &GameState, first: (usize, usize), second: (usize, usize)) -> bool {
+    if state.game_over || state.show_observation || state.turn_complete() || state.opening != OpeningStage::Done ||
+       first == second {
+        return false;
+    }
+    let renju = state.config.renju_active() && state.current_player == Player::Black;
+    [first, second].iter().all(|&(row, col)| {
+        state.board[row][col] == ProbPiece::Empty && !(renju && renju::is_forbidden(&state.board, row, col))
+    })
+}
+
+// 在两个交叉点各落一枚棋子（只算一手），落子后退出两点落子
+fn place_two(state: &mut GameState, stones: [((usize, usize), ProbPiece); 2]) {
+    for ((row, col), piece) in stones {
+        state.board[row][col] = piece;
+        state.lines.set(row, col, piece);
+        state.moves.push(MoveRecord { player: state.current_player, row, col, piece });
+    }
+    state.current_turn_move_count += 1;
+    state.show_prob_hint = !state.turn_complete();
+    state.pair_move = None;
+    state.pending_pair = None;
+}
+
+// 落下一对纠缠棋子：第二枚的概率与第一枚相同或互补
+fn place_pair(state: &mut GameState, first: (usize, usize), second: (usize, usize)) -> bool {
+    let same = match state.config.entangle {
+        EntangleRule::Off => return false,
+        EntangleRule::Opposite => false,
+        EntangleRule::Same => true,
+    };
+    if !can_place_pair(state, first, second) {
+        return false;
+    }
+    let piece = get_current_prob_piece(state);
+    let pair = Entanglement { move_index: state.moves.len(), first, second, same };
+    state.correlations.entanglements.push(pair);
+    place_two(state, [(first, piece), (second, pair.second_piece(piece))]);
+    true
+}
+
+// 落下一枚叠加棋子：同一枚棋子同时占据两个交叉点
+fn place_superposed(state: &mut GameState, first: (usize, usize), second: (usize, usize)) -> bool {
+    if !state.config.superposition || !can_place_pair(state, first, second) {
+        return false;
+    }
+    let piece = ProbPiece::Superposed(get_current_prob_piece(state).black_percent());
+    state.correlations.superpositions.push(Superposition { move_index: state.moves.len(), points: [first, second] });
+    place_two(state, [(first, piece), (second, piece)]);
+    true
+}
+
+// 开始或取消某种两点落子
+fn toggle_pair_move(state: &mut GameState, pair_move: PairMove) {
+    state.pair_move = if state.pair_move == Some(pair_move) { None } else { Some(pair_move) };
+    state.pending_pair = None;
+    state.active_card = None;
+}
+
+// 能力卡的目标棋子是否有效：本回合尚未用卡且尚未落满，目标为未观测的棋子，移除只能针对对方的棋子
+fn can_play_card(state: &GameState, card: PowerCard, (row, col): (usize, usize)) -> bool {
+    if state.game_over || state.show_observation || state.opening != OpeningStage::Done || state.turn_complete() ||
+       state.card_used || state.cards[state.current_player as usize][card as usize] == 0 {
+        return false;
+    }
+    if state.board[row][col] == ProbPiece::Empty || state.observed.contains(&(row, col)) {
+        return false;
+    }
+    let owner = state.moves.iter().rev().find(|mv| (mv.row, mv.col) == (row, col)).map(|mv| mv.player);
+    card != PowerCard::Remove || owner == Some(state.current_player.opponent())
+}
+
+// 与某点的棋子同属一个关联组的全部交叉点（叠加棋子的两个点、纠缠对的两枚棋子）
+fn linked_points(state: &GameState, point: (usize, usize)) -> Vec<(usize, usize)> {
+    let correlations = &state.correlations;
+    if let Some(stone) = correlations.superpositions.iter().find(|stone| stone.points.contains(&point)) {
+        return stone.points.to_vec();
+    }
+    match correlations.entanglements.iter().find(|pair| pair.first == point || pair.second == point) {
+        Some(pair) => vec![pair.first, pair.second],
+        None => vec![point],
+    }
+}
+
+// 解除某点所在的关联组，移入已解除列表
+fn retire_correlations(state: &mut GameState, point: (usize, usize)) {
+    let Correlations { entanglements, superpositions } = &mut state.correlations;
+    let retired = &mut state.retired;
+    entanglements.retain(|pair| {
+        let linked = pair.first == point || pair.second == point;
+        if linked {
+            retired.entanglements.push(*pair);
+        }
+        !linked
+    });
+    superpositions.retain(|stone| {
+        let linked = stone.points.contains(&point);
+        if linked {
+            retired.superpositions.push(*stone);
+        }
+        !linked
+    });
+}
+
+// 设置某点的棋子，同步更新连线窗口统计
+fn set_point(state: &mut GameState, (row, col): (usize, usize), piece: ProbPiece) {
+    state.board[row][col] = piece;
+    state.lines.set(row, col, piece);
+}
+
+// 对某点的棋子使用能力卡：坍缩卡在此时决定结果（叠加棋子先决定出现在哪一点），返回是否使用成功
+fn play_card(state: &mut GameState, card: PowerCard, target: (usize, usize)) -> bool {
+    if !can_play_card(state, card, target) {
+        return false;
+    }
+    let mut rng = thread_rng();
+    let (target, colour) = match card {
+        PowerCard::Collapse => {
+            let points = linked_points(state, target);
+            let kept = match state.board[target.0][target.1] {
+                ProbPiece::Superposed(_) => points[rng.gen_range(0..points.len())],
+                _ => target,
+            };
+            let colour = match prob_to_definite(ProbPiece::Stone(state.board[kept.0][kept.1].black_percent())) {
+                DefinitePiece::Black => Player::Black,
+                _ => Player::White,
+            };
+            (kept, Some(colour))
+        }
+        _ => (target, None),
+    };
+    apply_card(state, CardRecord { turn: state.turn, move_index: state.moves.len(), card, target, colour });
+    true
+}
+
+// 执行一次能力卡（结果已定）：移除、翻转或坍缩目标棋子及其关联组
+fn apply_card(state: &mut GameState, play: CardRecord) {
+    let points = linked_points(state, play.target);
+    match play.card {
+        PowerCard::Remove => {
+            // 叠加棋子两个点一起移除，纠缠对只移除目标一枚
+            let superposed = matches!(state.board[play.target.0][play.target.1], ProbPiece::Superposed(_));
+            let removed = if superposed { points } else { vec![play.target] };
+            for point in removed {
+                set_point(state, point, ProbPiece::Empty);
+            }
+            retire_correlations(state, play.target);
+        }
+        PowerCard::Flip => {
+            // 关联组一起翻转，纠缠关系保持不变
+            for (row, col) in points {
+                let piece = match state.board[row][col] {
+                    ProbPiece::Stone(percent) => ProbPiece::Stone(100 - percent),
+                    ProbPiece::Superposed(percent) => ProbPiece::Superposed(100 - percent),
+                    ProbPiece::Empty => ProbPiece::Empty,
+                };
+                set_point(state, (row, col), piece);
+            }
+        }
+        PowerCard::Collapse => {
+            // 叠加棋子留在目标点；纠缠对的另一枚随之确定
+            let colour = play.colour.unwrap_or(Player::Black);
+            let definite = |player: Player| ProbPiece::Stone(if player == Player::Black { 100 } else { 0 });
+            let partner = state
+                .correlations
+                .entanglements
+                .iter()
+                .find(|pair| pair.first == play.target || pair.second == play.target)
+                .copied();
+            for point in points {
+                let piece = match partner {
+                    _ if point == play.target => definite(colour),
+                    Some(pair) if !pair.same => definite(colour.opponent()),
+                    Some(_) => definite(colour),
+                    None => ProbPiece::Empty,
+                };
+                set_point(state, point, piece);
+                if piece != ProbPiece::Empty {
+                    state.observed.push(point);
+                }
+            }
+            retire_correlations(state, play.target);
+        }
+    }
+    state.cards[state.current_player as usize][play.card as usize] -= 1;
+    state.card_used = true;
+    state.active_card = None;
+    state.cards_played.push(play);
+}
+
+// 选中或取消选中一张能力卡（与两点落子互斥）
+fn toggle_card(state: &mut GameState, card: PowerCard) {
+    state.active_card = if state.active_card == Some(card) { None } else { Some(card) };
+    state.pair_move = None;
+    state.pending_pair = None;
+}
+
+// 由对局记录恢复某回合内落子之前的能力卡与预览（预览代替落子结束回合后，继续恢复下一回合的）
+fn replay_turn_actions(
+    state: &mut GameState,
+    previews: &mut Peekable<Iter<usize>>,
+    cards: &mut Peekable<Iter<CardRecord>>,
+) {
+    loop {
+        let turn = state.turn;
+        while let Some(&play) = cards.next_if(|play| (play.turn, play.move_index) == (state.turn, state.moves.len())) {
+            if can_play_card(state, play.card, play.target) {
+                apply_card(state, play);
+            }
+        }
+        replay_previews(state, previews);
+        if state.turn == turn {
+            break;
+        }
+    }
+}
+
+// 在某点落子：两点落子时先选第一个交叉点（再点一次取消），选第二个交叉点时一并落下
+fn place_at(state: &mut GameState, row: usize, col: usize) -> bool {
+    if let Some(card) = state.active_card {
+        play_card(state, card, (row, col));
+        return false;
+    }
+    let Some(pair_move) = state.pair_move else {
+        return place_piece(state, row, col);
+    };
+    match state.pending_pair {
+        Some(first) if first == (row, col) => state.pending_pair = None,
+        Some(first) if pair_move == PairMove::Entangle => return place_pair(state, first, (row, col)),
+        Some(first) => return place_superposed(state, first, (row, col)),
+        None if state.board[row][col] == ProbPiece::Empty => state.pending_pair = Some((row, col)),
+        None => {}
+    }
+    false
+}
+
+// 坍缩整个棋盘：叠加棋子先决定出现在哪一点，再让每枚棋子独立决定颜色，最后纠缠对中的第二枚跟随第一枚
+fn collapse_board(board: &[Vec<ProbPiece>], correlations: &Correlations) -> Vec<Vec<DefinitePiece>> {
+    let mut rng = thread_rng();
+    let mut resolved = board.to_vec();
+    for stone in &correlations.superpositions {
+        let [first, second] = stone.points;
+        let (kept, vacated) = if rng.gen_bool(0.5) { (first, second) } else { (second, first) };
+        resolved[kept.0][kept.1] = ProbPiece::Stone(resolved[kept.0][kept.1].black_percent());
+        resolved[vacated.0][vacated.1] = ProbPiece::Empty;
+    }
+    let mut collapsed: Vec<Vec<DefinitePiece>> = resolved
+        .iter()
+        .map(|line| line.iter().map(|&piece| prob_to_definite(piece)).collect())
+        .collect();
+    for pair in &correlations.entanglements {
+        let ((row, col), (second_row, second_col)) = (pair.first, pair.second);
+        collapsed[second_row][second_col] = pair.second_colour(collapsed[row][col]);
+    }
+    collapsed
+}
+
+// 预览棋盘：将所有概率棋子随机坍缩并检查获胜者
+fn observe_board(state: &mut GameState) {
+    if !state.can_preview() {
+        return;
+    }
+    spend_preview(state);
+    let win = state.config.win_condition();
+    let mut rerolls = 0;
+    let (winner, winning_pieces) = loop {
+        state.observation_board = collapse_board(&state.board, &state.correlations);
+        let (winner, winning_pieces) = check_winner(&state.observation_board, win);
+        if winner != Some(Outcome::BothWin) || state.config.double_win != DoubleWinRule::Reroll || rerolls == REROLL_LIMIT {
+            break (winner, winning_pieces);
+        }
+        rerolls += 1;
+    };
+    let winner = match winner {
+        Some(Outcome::BothWin) => Some(resolve_double_win(state.config.double_win, state.current_player, &winning_pieces)),
+        winner => winner,
+    };
+    state.observation_winner = winner;
+    state.winning_pieces = winning_pieces;
+    state.show_observation = true;
+
+    if winner.is_some() {
+        state.game_over = true;
+    }
+}
+
+// 按设置判定双方同时成五的结果（observer 为预览的一方）
+fn resolve_double_win(rule: DoubleWinRule, observer: Player, winning_pieces: &WinningPieces) -> Outcome {
+    match rule {
+        DoubleWinRule::ObserverLoses => Outcome::Win(observer.opponent()),
+        DoubleWinRule::ObserverWins => Outcome::Win(observer),
+        DoubleWinRule::CountLines => match winning_pieces.black.len().cmp(&winning_pieces.white.len()) {
+            std::cmp::Ordering::Greater => Outcome::Win(Player::Black),
+            std::cmp::Ordering::Less => Outcome::Win(Player::White),
+            std::cmp::Ordering::Equal => Outcome::BothWin,
+        },
+        DoubleWinRule::Draw | DoubleWinRule::Reroll => Outcome::BothWin,
+    }
+}
+
+// 扣除一次预览并记下所在回合；预览代替落子时本回合随即落满
+fn spend_preview(state: &mut GameState) {
+    state.observe_remaining[state.current_player as usize] -= 1;
+    state.previews_used.push(state.turn);
+    if state.config.preview_rule == PreviewRule::CostsPlacement {
+        state.placement_forfeited = true;
+        state.show_prob_hint = false;
+    }
+}
+
+// 由对局记录恢复某回合内的预览：预览代替了落子时直接结束该回合
+fn replay_previews(state: &mut GameState, previews: &mut Peekable<Iter<usize>>) {
+    while previews.next_if(|&&turn| turn == state.turn).is_some() {
+        if state.previews_left() == 0 {
+            continue;
+        }
+        spend_preview(state);
+        if state.placement_forfeited && previews.peek() != Some(&&state.turn) {
+            end_turn(state);
+        }
+    }
+}
+
+// 回合开始时按预览规则为当前玩家发放预览次数
+fn grant_previews(state: &mut GameState) {
+    let remaining = &mut state.observe_remaining[state.current_player as usize];
+    *remaining = match state.config.preview_rule {
+        PreviewRule::PerTurn | PreviewRule::CostsPlacement => state.config.previews,
+        PreviewRule::PerGame => *remaining,
+        PreviewRule::Bank => (*remaining + state.config.previews).min(PREVIEW_BANK_LIMIT),
+    };
+}
+
+// 概率漂移：本回合之前落下的棋子向50%或落子方漂移（预览不会真正坍缩棋子，所有棋子都未被观测）
+fn drift_stones(state: &mut GameState) {
+    let (step, toward_owner) = match state.config.drift {
+        Drift::Off => return,
+        Drift::Even(step) => (step, false),
+        Drift::Owner(step) => (step, true),
+    };
+    // 纠缠对与叠加棋子只漂移第一个点，第二个点随后按第一个点重新计算
+    let settled = state.turn_start;
+    let correlations = &state.correlations;
+    let mut skipped = vec![false; state.moves.len()];
+    for second in correlations
+        .entanglements
+        .iter()
+        .map(|pair| pair.move_index + 1)
+        .chain(correlations.superpositions.iter().map(|stone| stone.move_index + 1))
+    {
+        skipped[second] = true;
+    }
+    // 每个点上最后一手的下标：被移除后又有新落子的点只按最后一手计
+    let size = state.board.len();
+    let mut last_move = vec![vec![None; size]; size];
+    for (index, mv) in state.moves.iter().enumerate() {
+        last_move[mv.row][mv.col] = Some(index);
+    }
+    // 已被能力卡坍缩的棋子不再漂移
+    for &(row, col) in &state.observed {
+        last_move[row][col] = None;
+    }
+    for index in (0..settled).filter(|&index| !skipped[index]) {
+        let MoveRecord { player, row, col, .. } = state.moves[index];
+        if last_move[row][col] != Some(index) {
+            continue;
+        }
+        let target = match (toward_owner, player) {
+            (false, _) => 50,
+            (true, Player::Black) => 100,
+            (true, Player::White) => 0,
+        };
+        let piece = state.board[row][col].drifted(target, step);
+        state.board[row][col] = piece;
+        state.lines.set(row, col, piece);
+    }
+    for pair in state.correlations.entanglements.clone() {
+        let ((row, col), (second_row, second_col)) = (pair.first, pair.second);
+        let piece = pair.second_piece(state.board[row][col]);
+        state.board[second_row][second_col] = piece;
+        state.lines.set(second_row, second_col, piece);
+    }
+    for stone in state.correlations.superpositions.clone() {
+        let [(row, col), (second_row, second_col)] = stone.points;
+        let piece = state.board[row][col];
+        state.board[second_row][second_col] = piece;
+        state.lines.set(second_row, second_col, piece);
+    }
+}
+
+// 结束回合：本回合已落满时才交给对方
+fn end_turn(state: &mut GameState) {
+    if !state.turn_complete() {
+        return;
+    }
+    pass_turn(state);
+}
+
+// 切换玩家并重置本回合状态（不检查本回合是否已落子）
+fn pass_turn(state: &mut GameState) {
+    if state.opening == OpeningStage::Done {
+        drift_stones(state);
+    }
+    switch_player_prob(state);
+    // 开局结束后才计时，加秒也从正式对局开始
+    if state.opening == OpeningStage::Done {
+        match state.config.time_bonus {
+            TimeBonus::Fischer(seconds) if state.config.time_minutes > 0 => {
+                state.clocks[state.current_player as usize] += seconds as f32;
+            }
+            TimeBonus::ByoYomi(_, seconds) => state.period_clock = seconds as f32,
+            _ => {}
+        }
+    }
+    state.turn += 1;
+    state.turn_start = state.moves.len();
+    state.current_player = state.current_player.opponent();
+    state.placement_forfeited = false;
+    state.pair_move = None;
+    state.pending_pair = None;
+    state.card_used = false;
+    state.active_card = None;
+    state.show_observation = false;
+    state.observation_winner = None;
+    state.winning_pieces = WinningPieces::default();
+    state.current_turn_move_count = 0;
+    state.show_prob_hint = true;
+    state.end_turn_pending = false;
+    state.restart_pending = false;
+    state.computer_wait = COMPUTER_MOVE_DELAY;
+    state.opening = match state.opening {
+        OpeningStage::Placing if state.moves.len() >= OPENING_STONES => OpeningStage::SecondChooses,
+        OpeningStage::Extending if state.moves.len() >= SWAP2_STONES => OpeningStage::FirstChooses,
+        stage => stage,
+    };
+    // 开局阶段不能预览，预览次数从正式对局开始发放
+    if state.opening == OpeningStage::Done {
+        grant_previews(state);
+    }
+}
+
+// 开局中的颜色选择：返回该选择在当前阶段是否有效
+fn apply_opening_choice(state: &mut GameState, choice: OpeningChoice) -> bool {
+    match (state.opening, choice) {
+        (OpeningStage::SecondChooses, OpeningChoice::Extend) if state.config.opening == OpeningRule::Swap2 => {
+            state.opening = OpeningStage::Extending;
+        }
+        // 后手方执黑或先手方执白时双方交换颜色
+        (OpeningStage::SecondChooses, OpeningChoice::Black) | (OpeningStage::FirstChooses, OpeningChoice::White) => {
+            state.swapped = true;
+            state.opening = OpeningStage::Done;
+            grant_previews(state);
+        }
+        (OpeningStage::SecondChooses, OpeningChoice::White) | (OpeningStage::FirstChooses, OpeningChoice::Black) => {
+            state.swapped = false;
+            state.opening = OpeningStage::Done;
+            grant_previews(state);
+        }
+        _ => return false,
+    }
+    state.opening_choices.push(choice);
+    state.computer_wait = COMPUTER_MOVE_DELAY;
+    true
+}
+
+// 电脑回合：等待片刻后视局面决定是否预览，再落子并结束回合
+fn update_computer_turn(state: &mut GameState) {
+    if state.game_over || state.paused || !state.is_computer_turn() || state.collapse.is_some() {
+        return;
+    }
+    state.computer_wait -= get_frame_time();
+    if state.computer_wait > 0.0 {
+        return;
+    }
+
+    if state.choosing_colour() {
+        let choice = match ai::choose_colour(&state.board) {
+            Player::Black => OpeningChoice::Black,
+            Player::White => OpeningChoice::White,
+        };
+        apply_opening_choice(state, choice);
+        return;
+    }
+
+    if !state.show_observation && state.can_preview() &&
+       ai::should_preview(&state.board, &state.correlations, state.current_player, &state.config) {
+        // 先展示预览结果，下次等待结束后再落子
+        observe_board(state);
+        state.computer_wait = COMPUTER_MOVE_DELAY * 2.0;
+        return;
+    }
+
+    state.show_observation = false;
+    while !state.turn_complete() {
+        let piece = get_current_prob_piece(state);
+        match ai::choose_move(&state.board, &state.correlations, &state.lines, piece, state.current_player, &state.config) {
+            Some((row, col)) if place_piece(state, row, col) => {}
+            _ => break,
+        }
+    }
+    // 落满后结束回合；无处可落（剩余空位全是黑方禁手）时提前让出回合，避免对局卡住
+    pass_turn(state);
+}
+
+// 更新对局时钟：主用时用完后进入读秒，用时耗尽则判负（交换开局阶段、暂停与坍缩动画播放时不计时）
+fn update_clock(state: &mut GameState) {
+    if !state.config.timed() || state.game_over || state.paused || state.collapse.is_some() ||
+       state.opening != OpeningStage::Done {
+        return;
+    }
+    tick_clock(state, get_frame_time());
+}
+
+// 当前玩家的时钟走过 elapsed 秒
+fn tick_clock(state: &mut GameState, elapsed: f32) {
+    let index = state.current_player as usize;
+    let main_time = state.clocks[index];
+    state.clocks[index] = (main_time - elapsed).max(0.0);
+    let out_of_time = match state.config.time_bonus {
+        _ if state.clocks[index] > 0.0 => false,
+        TimeBonus::ByoYomi(_, seconds) => {
+            // 主用时在本帧中途用完时，超出的部分计入读秒
+            state.period_clock -= (elapsed - main_time).max(0.0);
+            while state.period_clock <= 0.0 && state.periods[index] > 0 {
+                state.periods[index] -= 1;
+                state.period_clock += seconds as f32;
+            }
+            state.periods[index] == 0
+        }
+        _ => true,
+    };
+    if out_of_time {
+        state.game_over = true;
+        state.show_observation = false;
+        state.observation_winner = Some(Outcome::Timeout(state.current_player));
+    }
+}
+
+// 处理键盘光标：移动光标或在光标处落子
+fn handle_cursor_keys(state: &mut GameState) {
+    if state.game_over || state.paused || state.is_computer_turn() {
+        return;
+    }
+
+    let size = state.board.len() as i32;
+    let moves = [
+        (KeyCode::Up, (-1, 0)),
+        (KeyCode::W, (-1, 0)),
+        (KeyCode::Down, (1, 0)),
+        (KeyCode::S, (1, 0)),
+        (KeyCode::Left, (0, -1)),
+        (KeyCode::A, (0, -1)),
+        (KeyCode::Right, (0, 1)),
+        (KeyCode::D, (0, 1)),
+    ];
+    for (key, (dr, dc)) in moves {
+        if is_key_pressed(key) {
+            let (row, col) = state.cursor;
+            let row = (row as i32 + dr).clamp(0, size - 1) as usize;
+            let col = (col as i32 + dc).clamp(0, size - 1) as usize;
+            state.cursor = (row, col);
+            state.cursor_visible = true;
+        }
+    }
+
+    if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) {
+        state.cursor_visible = true;
+        let (row, col) = state.cursor;
+        place_at(state, row, col);
+    }
+}
+
+// 检查棋盘获胜者：返回获胜者和全部获胜连线（按获胜条件决定连线长度与长连是否获胜）
+fn check_winner(board: &[Vec<DefinitePiece>], win: WinCondition) -> (Option<Outcome>, WinningPieces) {
     let directions = [(0, 1), (1, 0), (1, 1), (1, -1)];
-    let mut black_has_win = false;
-    let mut white_has_win = false;
     let mut winning_pieces = WinningPieces::default();
-    
-    for row in 0..BOARD_SIZE {
-        for col in 0..BOARD_SIZE {
-            let current = board[row][col];
+    let size = board.len() as i32;
+    let piece_at = |r: i32, c: i32| {
+        if r < 0 || r >= size || c < 0 || c >= size {
+            DefinitePiece::Empty
+        } else {
+            board[r as usize][c as usize]
+        }
+    };
+
+    for (row, line) in board.iter().enumerate() {
+        for (col, &current) in line.iter().enumerate() {
             if current == DefinitePiece::Empty {
                 continue;
             }
-            
+
             for (dr, dc) in directions {
-                let mut count = 1;
-                let mut current_win_pieces = vec![(row, col)];
-                
-                for step in 1..5 {
-                    let r = row as i32 + dr * step;
-                    let c = col as i32 + dc * step;
-                    
-                    if r < 0 || r >= BOARD_SIZE as i32 || c < 0 || c >= BOARD_SIZE as i32 {
-                        break;
-                    }
-                    
-                    let (r, c) = (r as usize, c as usize);
-                    if board[r][c] == current {
-                        count += 1;
-                        current_win_pieces.push((r, c));
-                    } else {
-                        break;
-                    }
+                // 只从连线的起点开始统计，保证每条连线只记录一次
+                let (row, col) = (row as i32, col as i32);
+                if piece_at(row - dr, col - dc) == current {
+                    continue;
                 }
-                
-                if count >= 5 {
-                    if current == DefinitePiece::Black && !black_has_win {
-                        black_has_win = true;
-                        winning_pieces.black = current_win_pieces;
-                    } else if current == DefinitePiece::White && !white_has_win {
-                        white_has_win = true;
-                        winning_pieces.white = current_win_pieces;
-                    }
+
+                let mut run = Vec::new();
+                let (mut r, mut c) = (row, col);
+                while piece_at(r, c) == current {
+                    run.push((r as usize, c as usize));
+                    r += dr;
+                    c += dc;
+                }
+
+                match current {
+                    DefinitePiece::Black if win.wins(Player::Black, run.len()) => winning_pieces.black.push(run),
+                    DefinitePiece::White if win.wins(Player::White, run.len()) => winning_pieces.white.push(run),
+                    _ => {}
                 }
             }
         }
     }
-    
+
+    let black_has_win = !winning_pieces.black.is_empty();
+    let white_has_win = !winning_pieces.white.is_empty();
     let result = if black_has_win && white_has_win {
-        Some("Draw! Both Players Win!")
+        Some(Outcome::BothWin)
     } else if black_has_win {
-        Some("Black Wins!")
+        Some(Outcome::Win(Player::Black))
     } else if white_has_win {
-        Some("White Wins!")
+        Some(Outcome::Win(Player::White))
     } else {
         let is_full = board.iter().all(|row| row.iter().all(|&p| p != DefinitePiece::Empty));
         if is_full {
-            Some("Draw! Board Full!")
+            Some(Outcome::BoardFull)
         } else {
             None
         }
@@ -213,658 +1463,1818 @@ fn check_winner(board: &[Vec<DefinitePiece>]) -> (Option<&'static str>, WinningP
     (result, winning_pieces)
 }
 
-// 绘制棋盘：包括网格线和星位点
-fn draw_board() {
-    for col in 0..BOARD_SIZE {
-        let x = BOARD_OFFSET_X + col as f32 * CELL_SIZE;
+// 星位点：四角星位与天元（小棋盘离边2路，其余离边3路）
+fn star_positions(size: usize) -> Vec<(usize, usize)> {
+    let edge = if size < 13 { 2 } else { 3 };
+    let far = size - 1 - edge;
+    let center = size / 2;
+    vec![(edge, edge), (edge, far), (center, center), (far, edge), (far, far)]
+}
+
+// 绘制棋盘：包括棋盘底面、网格线和星位点
+fn draw_board(layout: &Layout, theme: &Theme, show_coordinates: bool) {
+    let scale = layout.scale;
+    let padding = layout.cell_size * 0.45;
+    theme.draw_board_surface(
+        layout.board_x - padding,
+        layout.board_y - padding,
+        layout.board_extent + padding * 2.0,
+    );
+
+    for col in 0..layout.board_size {
+        let (x, _) = layout.intersection(0, col);
         draw_line(
             x,
-            BOARD_OFFSET_Y,
+            layout.board_y,
             x,
-            BOARD_OFFSET_Y + CELL_SIZE * (BOARD_SIZE - 1) as f32,
-            3.0 * SCALE,
-            DARKGRAY,
+            layout.board_bottom(),
+            3.0 * scale,
+            theme.grid,
         );
     }
     
-    for row in 0..BOARD_SIZE {
-        let y = BOARD_OFFSET_Y + row as f32 * CELL_SIZE;
+    for row in 0..layout.board_size {
+        let (_, y) = layout.intersection(row, 0);
         draw_line(
-            BOARD_OFFSET_X,
+            layout.board_x,
             y,
-            BOARD_OFFSET_X + CELL_SIZE * (BOARD_SIZE - 1) as f32,
+            layout.board_x + layout.board_extent,
             y,
-            3.0 * SCALE,
-            DARKGRAY,
+            3.0 * scale,
+            theme.grid,
         );
     }
     
-    let star_positions = [(3, 3), (3, 11), (7, 7), (11, 3), (11, 11)];
-    for (row, col) in star_positions {
-        let x = BOARD_OFFSET_X + col as f32 * CELL_SIZE;
-        let y = BOARD_OFFSET_Y + row as f32 * CELL_SIZE;
+    for (row, col) in star_positions(layout.board_size) {
+        let (x, y) = layout.intersection(row, col);
         draw_circle(
             x,
             y,
-            6.0 * SCALE,
-            BLACK,
+            6.0 * scale,
+            theme.star,
+        );
+    }
+
+    if show_coordinates {
+        draw_coordinates(layout, theme);
+    }
+}
+
+// 绘制棋盘坐标：上方为字母列号，左侧为数字行号（自下而上）
+fn draw_coordinates(layout: &Layout, theme: &Theme) {
+    let text_size = (layout.cell_size * 0.45).max(12.0 * layout.scale);
+    let margin = layout.cell_size * 0.6 + 6.0 * layout.scale;
+    for index in 0..layout.board_size {
+        let (x, y) = layout.intersection(index, index);
+
+        let letter = ((b'A' + index as u8) as char).to_string();
+        let letter_width = measure_ui_text(&letter, text_size).width;
+        draw_ui_text(&letter, x - letter_width / 2.0, layout.board_y - margin, text_size, theme.coordinates);
+
+        let number = (layout.board_size - index).to_string();
+        let number_width = measure_ui_text(&number, text_size).width;
+        draw_ui_text(
+            &number,
+            layout.board_x - margin - number_width / 2.0,
+            y + text_size / 3.0,
+            text_size,
+            theme.coordinates,
         );
     }
 }
 
 // 绘制概率棋子：根据概率显示不同颜色和百分比文本
-fn draw_prob_pieces(board: &[Vec<ProbPiece>]) {
-    for row in 0..BOARD_SIZE {
-        for col in 0..BOARD_SIZE {
-            let piece = board[row][col];
+fn draw_prob_pieces(layout: &Layout, theme: &Theme, settings: &Settings, board: &[Vec<ProbPiece>]) {
+    for (row, line) in board.iter().enumerate() {
+        for (col, &piece) in line.iter().enumerate() {
             if piece == ProbPiece::Empty {
                 continue;
             }
             
-            let x = BOARD_OFFSET_X + col as f32 * CELL_SIZE;
-            let y = BOARD_OFFSET_Y + row as f32 * CELL_SIZE;
-
-            let color = match piece {
-                ProbPiece::Black90 => Color::new(0.1, 0.1, 0.1, 1.0),
-                ProbPiece::Black70 => Color::new(0.3, 0.3, 0.3, 1.0),
-                ProbPiece::Black30 => Color::new(0.6, 0.6, 0.6, 1.0),
-                ProbPiece::Black10 => Color::new(0.8, 0.8, 0.8, 1.0),
-                ProbPiece::Empty => continue,
-            };
+            let (x, y) = layout.intersection(row, col);
+
+            // 叠加棋子总是画成半透明的普通棋子
+            if settings.split_stones && matches!(piece, ProbPiece::Stone(_)) {
+                theme.draw_split_stone(
+                    x,
+                    y,
+                    layout.piece_radius,
+                    piece.black_percent() as f32 / 100.0,
+                );
+            } else {
+                theme.draw_prob_stone(
+                    x,
+                    y,
+                    layout.piece_radius,
+                    piece,
+                );
+            }
 
-            draw_circle(
-                x,
-                y,
-                PIECE_RADIUS,
-                color,
-            );
+            if !settings.show_prob_text {
+                continue;
+            }
 
-            let text = match piece {
-                ProbPiece::Black90 => "90%",
-                ProbPiece::Black70 => "70%",
-                ProbPiece::Black30 => "30%",
-                ProbPiece::Black10 => "10%",
-                _ => "",
-            };
-            let text_size = 22.0 * SCALE;
-            let text_width = measure_text(text, None, text_size as u16, 1.0).width;
-            draw_text(
-                text,
+            let text = format!("{}%", piece.black_percent());
+            let text_size = layout.cell_size * 0.5;
+            let text_width = measure_ui_text(&text, text_size).width;
+            draw_ui_text(
+                &text,
                 x - text_width / 2.0,
                 y + text_size / 3.0,
                 text_size,
-                WHITE,
+                theme.prob_text,
             );
         }
     }
 }
 
-// 绘制落子预览：鼠标悬浮时显示即将落子的位置（深灰色半透明）
-fn draw_piece_preview(state: &GameState) {
-    if state.game_over || state.show_observation || state.current_turn_move_count > 0 {
+// 两点间的连线，dashed 时画成虚线
+fn draw_link(layout: &Layout, (x1, y1): (f32, f32), (x2, y2): (f32, f32), dashed: bool, color: Color) {
+    let thickness = 3.0 * layout.scale;
+    if !dashed {
+        draw_line(x1, y1, x2, y2, thickness, color);
         return;
     }
-    
+    let length = (x2 - x1).hypot(y2 - y1);
+    let segments = ((length / (8.0 * layout.scale)) as usize).max(1);
+    for index in (0..segments).step_by(2) {
+        let (from, to) = (index as f32 / segments as f32, (index + 1) as f32 / segments as f32);
+        draw_line(
+            x1 + (x2 - x1) * from,
+            y1 + (y2 - y1) * from,
+            x1 + (x2 - x1) * to,
+            y1 + (y2 - y1) * to,
+            thickness,
+            color,
+        );
+    }
+}
+
+// 绘制纠缠棋子对之间的连线（画在棋子下方）：同色为实线，异色为虚线
+fn draw_entanglements(layout: &Layout, theme: &Theme, entanglements: &[Entanglement]) {
+    for pair in entanglements {
+        let first = layout.intersection(pair.first.0, pair.first.1);
+        let second = layout.intersection(pair.second.0, pair.second.1);
+        draw_link(layout, first, second, !pair.same, theme.entangle);
+    }
+}
+
+// 局面变化后重新计算黑方禁手点（逐点判断棋形开销较大，不必每帧重算）
+fn refresh_forbidden_points(state: &mut GameState) {
+    let key = (state.moves.len(), state.cards_played.len(), state.turn);
+    if state.forbidden.as_ref().is_some_and(|(cached, _)| *cached == key) {
+        return;
+    }
+    let points = if state.config.renju_active() && state.current_player == Player::Black {
+        renju::forbidden_points(&state.board)
+    } else {
+        Vec::new()
+    };
+    state.forbidden = Some((key, points));
+}
+
+// 绘制落子预览：鼠标悬浮时显示即将落子的位置（半透明）
+fn draw_piece_preview(layout: &Layout, theme: &Theme, state: &GameState) {
+    if state.game_over || state.show_observation || state.turn_complete() || state.is_computer_turn() ||
+       state.choosing_colour() {
+        return;
+    }
+
+    // 连珠禁手：黑方回合在禁手点上画叉，悬停时不显示预览棋子
+    let forbidden = state.forbidden.as_ref().map_or(&[][..], |(_, points)| points);
+    let half = layout.piece_radius * 0.4;
+    for &(row, col) in forbidden {
+        let (x, y) = layout.intersection(row, col);
+        let thickness = 3.0 * layout.scale;
+        draw_line(x - half, y - half, x + half, y + half, thickness, theme.forbidden);
+        draw_line(x - half, y + half, x + half, y - half, thickness, theme.forbidden);
+    }
+
+    // 已选中能力卡：圈出所有可用的目标棋子，不画落子预览
+    if let Some(card) = state.active_card {
+        let size = state.board.len();
+        for (row, col) in (0..size).flat_map(|row| (0..size).map(move |col| (row, col))) {
+            if can_play_card(state, card, (row, col)) {
+                let (x, y) = layout.intersection(row, col);
+                draw_circle_lines(x, y, layout.piece_radius * 1.05, 2.0 * layout.scale, theme.hint_waiting);
+            }
+        }
+        return;
+    }
+
+    // 两点落子已选第一个点：在该点画预览棋子，纠缠落子时再连线到鼠标位置
     let (mouse_x, mouse_y) = mouse_position();
-    if let Some((row, col)) = mouse_to_grid(mouse_x, mouse_y) {
-        if state.board[row][col] == ProbPiece::Empty {
-            let x = BOARD_OFFSET_X + col as f32 * CELL_SIZE;
-            let y = BOARD_OFFSET_Y + row as f32 * CELL_SIZE;
-            let preview_color = Color::new(0.2, 0.2, 0.2, PREVIEW_ALPHA);
-            
+    if let Some((row, col)) = state.pending_pair {
+        let first = layout.intersection(row, col);
+        if state.pair_move == Some(PairMove::Entangle) {
+            let dashed = state.config.entangle == EntangleRule::Opposite;
+            draw_link(layout, first, (mouse_x, mouse_y), dashed, theme.entangle);
+        }
+        draw_circle(first.0, first.1, layout.piece_radius * 0.85, theme.preview_stone);
+    }
+    if let Some((row, col)) = layout.mouse_to_grid(mouse_x, mouse_y) {
+        if state.board[row][col] == ProbPiece::Empty && !forbidden.contains(&(row, col)) {
+            let (x, y) = layout.intersection(row, col);
             draw_circle(
                 x,
                 y,
-                PIECE_RADIUS - 4.0 * SCALE,
-                preview_color
+                layout.piece_radius * 0.85,
+                theme.preview_stone,
             );
         }
     }
 }
 
-// 绘制预览棋盘：显示确定棋子、获胜者和获胜棋子边框
+// 绘制键盘光标：与落子预览相同的半透明棋子，外加高亮方框
+fn draw_keyboard_cursor(layout: &Layout, theme: &Theme, state: &GameState) {
+    if !state.cursor_visible || state.game_over || state.show_observation {
+        return;
+    }
+
+    let (row, col) = state.cursor;
+    let (x, y) = layout.intersection(row, col);
+    if !state.turn_complete() && state.board[row][col] == ProbPiece::Empty {
+        draw_circle(
+            x,
+            y,
+            layout.piece_radius * 0.85,
+            theme.preview_stone,
+        );
+    }
+
+    let half = layout.cell_size * 0.45;
+    draw_rectangle_lines(
+        x - half,
+        y - half,
+        half * 2.0,
+        half * 2.0,
+        3.0 * layout.scale,
+        theme.cursor,
+    );
+}
+
+// 绘制预览棋盘：显示确定棋子和获胜棋子边框（动画播放中先翻转再落定，获胜连线依次亮起）
 fn draw_observation_board(
+    layout: &Layout,
+    theme: &Theme,
     board: &[Vec<DefinitePiece>],
-    winner: Option<&str>,
     winning_pieces: &WinningPieces,
+    animation: Option<&CollapseAnimation>,
 ) {
-    let bg_padding = 30.0 * SCALE;
-    let bg_color = Color::new(0.0, 0.0, 0.0, 0.3);
+    let scale = layout.scale;
+    let bg_padding = 30.0 * scale;
+    let bg_color = theme.observation_overlay;
     draw_rectangle(
-        BOARD_OFFSET_X - bg_padding,
-        BOARD_OFFSET_Y - bg_padding,
-        CELL_SIZE * (BOARD_SIZE - 1) as f32 + bg_padding * 2.0,
-        CELL_SIZE * (BOARD_SIZE - 1) as f32 + bg_padding * 2.0,
+        layout.board_x - bg_padding,
+        layout.board_y - bg_padding,
+        layout.board_extent + bg_padding * 2.0,
+        layout.board_extent + bg_padding * 2.0,
         bg_color,
     );
 
-    let observe_piece_radius = PIECE_RADIUS;
-    let win_border_width = 3.0 * SCALE;
-    let win_border_color = Color::new(1.0, 0.0, 0.0, 1.0);
+    let observe_piece_radius = layout.piece_radius;
+    let win_border_width = 3.0 * scale;
+    let win_border_color = theme.win_highlight;
+
+    for (row, line) in board.iter().enumerate() {
+        for (col, &piece) in line.iter().enumerate() {
+            if piece == DefinitePiece::Empty {
+                continue;
+            }
+            
+            let (x, y) = layout.intersection(row, col);
+            let color = match piece {
+                DefinitePiece::Black => theme.black_stone,
+                _ => theme.white_stone,
+            };
+
+            let settle = animation.map_or(1.0, |animation| animation.settle(row, col));
+            if settle >= 1.0 {
+                theme.draw_stone(x, y, observe_piece_radius, color);
+            } else if settle > 0.0 {
+                // 落定：以最终颜色由侧面转为正面
+                draw_ellipse(x, y, observe_piece_radius * settle.max(0.1), observe_piece_radius, 0.0, color);
+            } else {
+                // 翻转：像硬币一样旋转，正反两面为黑白两色
+                let time = animation.map_or(0.0, |animation| animation.time());
+                let phase = time * COLLAPSE_SPIN_RATE + (row * 7 + col * 13) as f32;
+                let spin_color = if phase.cos() > 0.0 { theme.black_stone } else { theme.white_stone };
+                let width = observe_piece_radius * phase.cos().abs().max(0.1);
+                draw_ellipse(x, y, width, observe_piece_radius, 0.0, spin_color);
+            }
+        }
+    }
+
+    // 获胜连线按顺序亮起，最后一枚渐显
+    let sweep = animation.map_or(1.0, |animation| animation.sweep());
+    for line in winning_pieces.lines(Player::Black).iter().chain(winning_pieces.lines(Player::White)) {
+        let lit = sweep * line.len() as f32;
+        for (index, (row, col)) in line.iter().enumerate() {
+            let alpha = (lit - index as f32).clamp(0.0, 1.0);
+            if alpha <= 0.0 {
+                break;
+            }
+            let (x, y) = layout.intersection(*row, *col);
+            draw_circle_lines(
+                x,
+                y,
+                observe_piece_radius - win_border_width / 2.0,
+                win_border_width,
+                Color { a: win_border_color.a * alpha, ..win_border_color },
+            );
+        }
+    }
+}
+
+// 绘制获胜者横幅：slide 为滑入进度，未完成时横幅从窗口上方滑下
+fn draw_winner_banner(layout: &Layout, theme: &Theme, winner_text: &str, slide: f32) {
+    let scale = layout.scale;
+    let text_size = 100.0 * scale;
+    let text_width = measure_ui_text(winner_text, text_size).width;
+    let text_height = text_size * 1.2;
+    let bg_padding = 15.0 * scale;
+
+    let text_x = layout.center_x() - text_width / 2.0;
+    let shown_y = layout.ui_y(50.0);
+    let text_y = shown_y - (1.0 - slide) * (shown_y + text_height + bg_padding);
+
+    draw_rectangle(
+        text_x - bg_padding,
+        text_y - bg_padding / 2.0,
+        text_width + bg_padding * 2.0,
+        text_height + bg_padding,
+        theme.banner,
+    );
+    draw_rectangle_lines(
+        text_x - bg_padding,
+        text_y - bg_padding / 2.0,
+        text_width + bg_padding * 2.0,
+        text_height + bg_padding,
+        4.0 * scale,
+        theme.banner_text,
+    );
+
+    draw_ui_text(
+        winner_text,
+        text_x,
+        text_y + text_size * 0.8,
+        text_size,
+        theme.banner_text,
+    );
+}
+
+// 构建当前画面的按钮：绘制与点击检测共用同一份布局
+fn game_buttons(layout: &Layout, strings: &Strings, state: &GameState) -> Vec<Button> {
+    let scale = layout.scale;
+    let menu_button = Button::new(
+        UiAction::Menu,
+        Rect::new(layout.left + 10.0 * scale, layout.ui_y(20.0), 90.0 * scale, 40.0 * scale),
+        strings.menu,
+        ButtonStyle {
+            color: Color::new(0.4, 0.4, 0.4, 0.9),
+            hover_color: Color::new(0.3, 0.3, 0.3, 0.9),
+            disabled_color: GRAY,
+            text_color: WHITE,
+            text_size: 22.0 * scale,
+            border_width: 0.0,
+        },
+    )
+    .shortcut(KeyCode::Escape);
+    // 计时对局右上角的暂停按钮；暂停时只保留菜单与继续
+    let pause_button = Button::new(
+        UiAction::TogglePause,
+        Rect::new(layout.left + layout.width - 100.0 * scale, layout.ui_y(20.0), 90.0 * scale, 40.0 * scale),
+        if state.paused { strings.resume } else { strings.pause },
+        menu_button.style,
+    )
+    .shortcut(KeyCode::Tab);
+
+    if state.game_over {
+        let button_y = layout.board_bottom() + layout.cell_size + 40.0 * scale;
+        let button_width = 180.0 * scale * GAME_OVER_BUTTON_SCALE;
+        let button_height = 60.0 * scale * GAME_OVER_BUTTON_SCALE;
+        let restart_x = layout.center_x() - (button_width * 2.0 + 60.0 * scale) / 2.0;
+        let exit_x = restart_x + button_width + 60.0 * scale;
+        let text_size = 28.0 * scale * GAME_OVER_BUTTON_SCALE;
+
+        let restart_style = ButtonStyle {
+            color: Color::new(0.0, 0.8, 0.0, 0.9),
+            hover_color: Color::new(0.1, 0.7, 0.1, 0.9),
+            disabled_color: GRAY,
+            text_color: WHITE,
+            text_size,
+            border_width: 4.0 * scale,
+        };
+        let exit_style = ButtonStyle {
+            color: Color::new(0.8, 0.0, 0.0, 0.9),
+            hover_color: Color::new(0.7, 0.1, 0.1, 0.9),
+            ..restart_style
+        };
+
+        return vec![
+            menu_button,
+            Button::new(
+                UiAction::Restart,
+                Rect::new(restart_x, button_y, button_width, button_height),
+                strings.restart_game,
+                restart_style,
+            )
+            .shortcut(KeyCode::R),
+            Button::new(
+                UiAction::Exit,
+                Rect::new(exit_x, button_y, button_width, button_height),
+                strings.exit_game,
+                exit_style,
+            ),
+        ];
+    }
 
-    for row in 0..BOARD_SIZE {
-        for col in 0..BOARD_SIZE {
-            let piece = board[row][col];
-            if piece == DefinitePiece::Empty {
-                continue;
-            }
-            
-            let x = BOARD_OFFSET_X + col as f32 * CELL_SIZE;
-            let y = BOARD_OFFSET_Y + row as f32 * CELL_SIZE;
+    let button_y = layout.ui_y(160.0);
+    let button_width = 160.0 * scale;
+    let button_height = 50.0 * scale;
+    // 开启纠缠落子或叠加落子时，在预览按钮左侧各多一个按钮
+    let mut pair_moves = Vec::new();
+    if state.config.entangle != EntangleRule::Off {
+        pair_moves.push((PairMove::Entangle, UiAction::ToggleEntangle, strings.entangle, KeyCode::E));
+    }
+    if state.config.superposition {
+        pair_moves.push((PairMove::Superpose, UiAction::ToggleSuperpose, strings.superpose, KeyCode::Q));
+    }
+    let pair_step = button_width + 40.0 * scale;
+    let pair_x = layout.center_x() -
+        (pair_step * pair_moves.len() as f32 + button_width + END_TURN_BUTTON_WIDTH * scale + 40.0 * scale) / 2.0;
+    let observe_x = pair_x + pair_step * pair_moves.len() as f32;
+    let end_turn_button_x = observe_x + button_width + 40.0 * scale;
+
+    let observe_style = ButtonStyle {
+        color: GREEN,
+        hover_color: Color::new(0.1, 0.7, 0.1, 0.9),
+        disabled_color: GRAY,
+        text_color: WHITE,
+        text_size: 22.0 * scale,
+        border_width: 0.0,
+    };
+    let end_turn_style = ButtonStyle {
+        color: Color::new(0.2, 0.4, 0.8, 0.9),
+        hover_color: Color::new(0.1, 0.3, 0.7, 0.9),
+        disabled_color: Color::new(0.5, 0.5, 0.5, 0.7),
+        ..observe_style
+    };
 
-            match piece {
-                DefinitePiece::Black => draw_circle(x, y, observe_piece_radius, BLACK),
-                DefinitePiece::White => draw_circle(x, y, observe_piece_radius, WHITE),
-                DefinitePiece::Empty => continue,
-            }
-        }
+    if state.paused {
+        return vec![menu_button, pause_button];
+    }
+    let mut buttons = vec![menu_button];
+    if state.config.timed() && state.opening == OpeningStage::Done {
+        buttons.push(pause_button);
     }
 
-    for (row, col) in &winning_pieces.black {
-        let x = BOARD_OFFSET_X + (*col as f32) * CELL_SIZE;
-        let y = BOARD_OFFSET_Y + (*row as f32) * CELL_SIZE;
-        draw_circle_lines(
-            x,
-            y,
-            observe_piece_radius - win_border_width / 2.0,
-            win_border_width,
-            win_border_color,
-        );
+    let human_turn = !state.is_computer_turn();
+    if state.choosing_colour() {
+        if !human_turn {
+            return buttons;
+        }
+        let mut choices = vec![
+            (UiAction::ChooseBlack, strings.play_black),
+            (UiAction::ChooseWhite, strings.play_white),
+        ];
+        if state.opening == OpeningStage::SecondChooses && state.config.opening == OpeningRule::Swap2 {
+            choices.push((UiAction::ChooseExtend, strings.place_two_more));
+        }
+        let spacing = 30.0 * scale;
+        let total = button_width * choices.len() as f32 + spacing * (choices.len() - 1) as f32;
+        let left = layout.center_x() - total / 2.0;
+        for (index, (action, label)) in choices.into_iter().enumerate() {
+            let x = left + index as f32 * (button_width + spacing);
+            buttons.push(Button::new(action, Rect::new(x, button_y, button_width, button_height), label, end_turn_style));
+        }
+        return buttons;
     }
-    for (row, col) in &winning_pieces.white {
-        let x = BOARD_OFFSET_X + (*col as f32) * CELL_SIZE;
-        let y = BOARD_OFFSET_Y + (*row as f32) * CELL_SIZE;
-        draw_circle_lines(
-            x,
-            y,
-            observe_piece_radius - win_border_width / 2.0,
-            win_border_width,
-            win_border_color,
+
+    let pair_style = ButtonStyle {
+        color: Color::new(0.55, 0.3, 0.75, 0.9),
+        hover_color: Color::new(0.45, 0.2, 0.65, 0.9),
+        ..observe_style
+    };
+    let can_pair = human_turn && state.opening == OpeningStage::Done && !state.turn_complete() && !state.show_observation;
+    for (index, (pair_move, action, label, key)) in pair_moves.into_iter().enumerate() {
+        let x = pair_x + pair_step * index as f32;
+        let label = if state.pair_move == Some(pair_move) { strings.cancel } else { label };
+        buttons.push(
+            Button::new(action, Rect::new(x, button_y, button_width, button_height), label, pair_style)
+                .enabled(can_pair)
+                .shortcut(key),
         );
     }
 
-    if let Some(winner_text) = winner {
-        let text_size = 100.0 * SCALE;
-        let text_width = measure_text(winner_text, None, text_size as u16, 1.0).width;
-        let text_height = text_size * 1.2;
+    // 能力卡栏：棋盘左侧一列，显示当前玩家各种卡的剩余张数
+    if state.config.power_cards > 0 {
+        let card_style = ButtonStyle { text_size: 20.0 * scale, ..pair_style };
+        let can_card = can_pair && !state.card_used;
+        let cards = [
+            (PowerCard::Remove, strings.card_remove, KeyCode::Key1),
+            (PowerCard::Flip, strings.card_flip, KeyCode::Key2),
+            (PowerCard::Collapse, strings.card_collapse, KeyCode::Key3),
+        ];
+        for (index, (card, label, key)) in cards.into_iter().enumerate() {
+            let left = state.cards[state.current_player as usize][card as usize];
+            let label = if state.active_card == Some(card) { strings.cancel.to_owned() } else { fill(label, &[&left]) };
+            let y = layout.board_y + index as f32 * 55.0 * scale;
+            buttons.push(
+                Button::new(
+                    UiAction::SelectCard(card),
+                    Rect::new(layout.left + 15.0 * scale, y, 140.0 * scale, 44.0 * scale),
+                    label,
+                    card_style,
+                )
+                .enabled(can_card && left > 0)
+                .shortcut(key),
+            );
+        }
+    }
 
-        let text_x = (WINDOW_WIDTH - text_width) / 2.0;
-        let text_y = 50.0 * SCALE;
+    let observe_text = if state.show_observation { strings.hide_preview } else { strings.preview_board };
+    buttons.extend([
+        Button::new(
+            UiAction::TogglePreview,
+            Rect::new(observe_x, button_y, button_width, button_height),
+            observe_text,
+            observe_style,
+        )
+        .enabled(human_turn && (state.can_preview() || state.show_observation))
+        .shortcut(KeyCode::P),
+        Button::new(
+            UiAction::EndTurn,
+            Rect::new(
+                end_turn_button_x,
+                button_y,
+                END_TURN_BUTTON_WIDTH * scale,
+                END_TURN_BUTTON_HEIGHT * scale,
+            ),
+            if state.end_turn_pending { strings.confirm_end_turn } else { strings.end_turn },
+            end_turn_style,
+        )
+        .enabled(human_turn && state.turn_complete())
+        .shortcut(KeyCode::Space),
+    ]);
+    buttons
+}
 
-        let bg_padding = 15.0 * SCALE;
-        draw_rectangle(
-            text_x - bg_padding,
-            text_y - bg_padding / 2.0,
-            text_width + bg_padding * 2.0,
-            text_height + bg_padding,
-            Color::new(1.0, 1.0, 0.0, 0.95),
-        );
-        draw_rectangle_lines(
-            text_x - bg_padding,
-            text_y - bg_padding / 2.0,
-            text_width + bg_padding * 2.0,
-            text_height + bg_padding,
-            4.0 * SCALE,
-            BLACK,
-        );
+// 用时显示为 分:秒
+fn format_clock(seconds: f32) -> String {
+    let total = seconds.ceil() as u32;
+    format!("{:02}:{:02}", total / 60, total % 60)
+}
 
-        draw_text(
-            winner_text,
-            text_x,
-            text_y + text_size * 0.8,
-            text_size,
-            BLACK,
-        );
+// 某方时钟的显示：主用时用完后显示读秒剩余时间与剩余次数
+fn clock_text(state: &GameState, player: Player) -> String {
+    let index = player as usize;
+    match state.config.time_bonus {
+        TimeBonus::ByoYomi(_, seconds) if state.clocks[index] == 0.0 => {
+            let period = if player == state.current_player { state.period_clock } else { seconds as f32 };
+            format!("{} ({})", format_clock(period), state.periods[index])
+        }
+        _ => format_clock(state.clocks[index]),
     }
 }
 
-// 绘制UI界面：包括当前玩家、棋子概率提示、预览按钮、结束回合按钮
-fn draw_ui(
-    show_observation: bool,
-    observe_remaining: u8,
-    current_player: Player,
-    current_prob_piece: ProbPiece,
-    game_over: bool,
-    current_turn_move_count: u8,
-    show_prob_hint: bool,
-) {
-    if game_over {
+// 绘制UI界面：包括当前玩家、棋子概率提示、剩余预览次数和落子提示（按钮由game_buttons提供）
+fn draw_ui(layout: &Layout, theme: &Theme, settings: &Settings, state: &GameState, buttons: &[Button]) {
+    let scale = layout.scale;
+    let strings = i18n::strings(settings.language);
+
+    if state.config.timed() {
+        let clock_size = 24.0 * scale;
+        for (player, label) in [(Player::Black, strings.black), (Player::White, strings.white)] {
+            let text = format!("{} {}", label, clock_text(state, player));
+            let width = measure_ui_text(&text, clock_size).width;
+            let x = match player {
+                Player::Black => layout.left + 10.0 * scale,
+                Player::White => layout.left + layout.width - width - 10.0 * scale,
+            };
+            let color = if player == state.current_player && !state.game_over {
+                theme.hint_waiting
+            } else {
+                theme.text
+            };
+            draw_ui_text(&text, x, layout.ui_y(95.0), clock_size, color);
+        }
+    }
+
+    if state.game_over {
+        return;
+    }
+    if state.paused {
+        let paused_size = 48.0 * scale;
+        let paused_width = measure_ui_text(strings.paused, paused_size).width;
+        let paused_y = (layout.board_y + layout.board_bottom()) / 2.0;
+        draw_rectangle(
+            layout.center_x() - paused_width / 2.0 - 20.0 * scale,
+            paused_y - paused_size,
+            paused_width + 40.0 * scale,
+            paused_size * 1.4,
+            theme.panel,
+        );
+        draw_ui_text(
+            strings.paused,
+            layout.center_x() - paused_width / 2.0,
+            paused_y,
+            paused_size,
+            theme.text,
+        );
         return;
     }
 
-    let (mouse_x, mouse_y) = mouse_position();
+    let current_prob_piece = get_current_prob_piece(state);
 
-    let (player_text, prob_text) = match current_player {
+    let colour_name = match state.current_player {
+        Player::Black => strings.black,
+        Player::White => strings.white,
+    };
+    let opening_text = match state.opening {
+        OpeningStage::Placing => Some(fill(strings.opening_first_places, &[&colour_name])),
+        OpeningStage::Extending => Some(fill(strings.opening_second_places, &[&colour_name])),
+        OpeningStage::SecondChooses => Some(strings.opening_second_chooses.to_owned()),
+        OpeningStage::FirstChooses => Some(strings.opening_first_chooses.to_owned()),
+        OpeningStage::Done => None,
+    };
+    let (turn_text, prob_text) = match state.current_player {
         Player::Black => (
-            "Current Turn: Black",
+            strings.current_turn_black,
             match current_prob_piece {
-                ProbPiece::Black90 => "Next Piece: 90% Black",
-                ProbPiece::Black70 => "Next Piece: 70% Black",
-                _ => "Next Piece: 90% Black",
+                ProbPiece::BLACK_90 => strings.next_black_90,
+                ProbPiece::BLACK_70 => strings.next_black_70,
+                _ => strings.next_black_90,
             },
         ),
         Player::White => (
-            "Current Turn: White",
+            strings.current_turn_white,
             match current_prob_piece {
-                ProbPiece::Black10 => "Next Piece: 90% White (10% Black)",
-                ProbPiece::Black30 => "Next Piece: 70% White (30% Black)",
-                _ => "Next Piece: 90% White (10% Black)",
+                ProbPiece::BLACK_10 => strings.next_white_90,
+                ProbPiece::BLACK_30 => strings.next_white_70,
+                _ => strings.next_white_90,
             },
         ),
     };
 
-    let player_text_size = 28.0 * SCALE;
-    let player_text_width = measure_text(player_text, None, player_text_size as u16, 1.0).width;
-    let player_bg_x = BOARD_OFFSET_X + (BOARD_ACTUAL_WIDTH - player_text_width) / 2.0 - 15.0 * SCALE;
+    let player_text = opening_text.as_deref().unwrap_or(turn_text);
+    let player_text_size = 28.0 * scale;
+    let player_text_width = measure_ui_text(player_text, player_text_size).width;
+    let player_bg_x = layout.center_x() - player_text_width / 2.0 - 15.0 * scale;
     draw_rectangle(
         player_bg_x,
-        20.0 * SCALE,
-        player_text_width + 30.0 * SCALE,
-        40.0 * SCALE,
-        Color::new(0.9, 0.9, 0.9, 0.8),
+        layout.ui_y(20.0),
+        player_text_width + 30.0 * scale,
+        40.0 * scale,
+        theme.panel,
     );
-    draw_text(
+    draw_ui_text(
         player_text,
-        player_bg_x + 15.0 * SCALE,
-        20.0 * SCALE + 30.0 * SCALE,
+        player_bg_x + 15.0 * scale,
+        layout.ui_y(20.0 + 30.0),
         player_text_size,
-        BLACK,
+        theme.text,
     );
 
-    if show_prob_hint {
-        let prob_text_size = 24.0 * SCALE;
-        let prob_text_width = measure_text(prob_text, None, prob_text_size as u16, 1.0).width;
-        let prob_bg_x = BOARD_OFFSET_X + (BOARD_ACTUAL_WIDTH - prob_text_width) / 2.0 - 15.0 * SCALE;
+    if settings.show_prob_text && state.show_prob_hint && !state.choosing_colour() {
+        let prob_text_size = 24.0 * scale;
+        let prob_text_width = measure_ui_text(prob_text, prob_text_size).width;
+        let prob_bg_x = layout.center_x() - prob_text_width / 2.0 - 15.0 * scale;
         draw_rectangle(
             prob_bg_x,
-            70.0 * SCALE,
-            prob_text_width + 30.0 * SCALE,
-            35.0 * SCALE,
-            Color::new(0.85, 0.85, 0.85, 0.8),
+            layout.ui_y(70.0),
+            prob_text_width + 30.0 * scale,
+            35.0 * scale,
+            theme.panel,
         );
-        draw_text(
+        draw_ui_text(
             prob_text,
-            prob_bg_x + 15.0 * SCALE,
-            70.0 * SCALE + 25.0 * SCALE,
+            prob_bg_x + 15.0 * scale,
+            layout.ui_y(70.0 + 25.0),
             prob_text_size,
-            BLACK,
+            theme.text,
         );
     }
 
-    let button_y = 160.0 * SCALE;
-    let button_width = 160.0 * SCALE;
-    let button_height = 50.0 * SCALE;
-
-    let observe_x = BOARD_OFFSET_X + (BOARD_ACTUAL_WIDTH - button_width - END_TURN_BUTTON_WIDTH * SCALE - 40.0 * SCALE) / 2.0;
-    let observe_text = if show_observation { "Hide Preview" } else { "Preview Board" };
-    let is_observe_hover = (observe_remaining > 0 || show_observation) &&
-        mouse_x >= observe_x && mouse_x <= observe_x + button_width &&
-        mouse_y >= button_y && mouse_y <= button_y + button_height;
-    let observe_color = if observe_remaining > 0 || show_observation {
-        if is_observe_hover { Color::new(0.1, 0.7, 0.1, 0.9) } else { GREEN }
-    } else {
-        GRAY
-    };
-    let observe_scale = if is_observe_hover { HOVER_SCALE } else { 1.0 };
-    let observe_draw_x = observe_x - (button_width * (observe_scale - 1.0)) / 2.0;
-    let observe_draw_y = button_y - (button_height * (observe_scale - 1.0)) / 2.0;
-
-    draw_rectangle(
-        observe_draw_x,
-        observe_draw_y,
-        button_width * observe_scale,
-        button_height * observe_scale,
-        observe_color,
-    );
-    let observe_text_size = 22.0 * SCALE;
-    let observe_text_width = measure_text(observe_text, None, observe_text_size as u16, 1.0).width;
-    draw_text(
-        observe_text,
-        observe_draw_x + (button_width * observe_scale - observe_text_width) / 2.0,
-        observe_draw_y + 30.0 * SCALE,
-        observe_text_size,
-        WHITE,
-    );
+    if let Some(observe_button) = find_button(buttons, UiAction::TogglePreview) {
+        let count_text = fill(strings.previews_left, &[&state.previews_left()]);
+        let count_text_size = 22.0 * scale;
+        draw_ui_text(
+            &count_text,
+            observe_button.rect.x + 4.0 * scale,
+            observe_button.rect.y - 10.0 * scale,
+            count_text_size,
+            theme.text,
+        );
+    }
 
-    let count_text = format!("Previews Left: {}", observe_remaining);
-    let count_text_size = 22.0 * SCALE;
-    let count_text_x = observe_x + 4.0 * SCALE;
-    draw_text(
-        &count_text,
-        count_text_x,
-        button_y - 10.0 * SCALE,
-        count_text_size,
-        BLACK,
-    );
+    if let Some(end_turn_button) = find_button(buttons, UiAction::EndTurn) {
+        let remaining = state.stones_this_turn().saturating_sub(state.current_turn_move_count);
+        let move_hint = if state.restart_pending {
+            strings.hint_restart.to_owned()
+        } else if state.is_computer_turn() {
+            strings.hint_computer.to_owned()
+        } else if state.turn_complete() {
+            strings.hint_end_turn.to_owned()
+        } else if state.active_card.is_some() {
+            strings.hint_pick_card.to_owned()
+        } else if state.pair_move.is_some() && state.pending_pair.is_none() {
+            strings.hint_pick_first.to_owned()
+        } else if state.pair_move.is_some() {
+            strings.hint_pick_second.to_owned()
+        } else if remaining == 1 {
+            strings.hint_place.to_owned()
+        } else {
+            fill(strings.hint_place_n, &[&remaining])
+        };
+        let move_hint_color = if state.turn_complete() {
+            theme.hint_ready
+        } else {
+            theme.hint_waiting
+        };
+        let move_hint_size = 24.0 * scale;
+        let move_hint_width = measure_ui_text(&move_hint, move_hint_size).width;
+        draw_ui_text(
+            &move_hint,
+            end_turn_button.rect.x + (end_turn_button.rect.w - move_hint_width) / 2.0,
+            end_turn_button.rect.y - 10.0 * scale,
+            move_hint_size,
+            move_hint_color,
+        );
+    }
+}
 
-    let move_hint = if current_turn_move_count > 0 {
-        "Click End Turn"
-    } else {
-        "Place a Piece"
+// 规则说明文本：随对局设置变化（编号与折行在绘制时处理）
+fn game_rule_lines(strings: &Strings, config: &GameConfig) -> Vec<String> {
+    let (black_pieces, white_pieces) = match config.tiers {
+        ProbTiers::Rotate => (strings.rule_black_rotate, strings.rule_white_rotate),
+        ProbTiers::StrongOnly => (strings.rule_black_strong, strings.rule_white_strong),
+        ProbTiers::WeakOnly => (strings.rule_black_weak, strings.rule_white_weak),
     };
-    let move_hint_color = if current_turn_move_count > 0 {
-        Color::new(0.0, 0.7, 0.0, 1.0)
-    } else {
-        Color::new(0.8, 0.0, 0.0, 1.0)
+    let previews = match config.previews {
+        1 => strings.times_once.to_owned(),
+        2 => strings.times_twice.to_owned(),
+        n => fill(strings.times_n, &[&n]),
     };
-    let move_hint_size = 24.0 * SCALE;
-    let move_hint_width = measure_text(move_hint, None, move_hint_size as u16, 1.0).width;
-    let end_turn_button_x = observe_x + button_width + 40.0 * SCALE;
-    let move_hint_x = end_turn_button_x + (END_TURN_BUTTON_WIDTH * SCALE - move_hint_width) / 2.0;
-    draw_text(
-        move_hint,
-        move_hint_x,
-        button_y - 10.0 * SCALE,
-        move_hint_size,
-        move_hint_color,
-    );
-
-    let end_turn_button_enabled = current_turn_move_count > 0;
-    let is_end_turn_hover = end_turn_button_enabled &&
-        mouse_x >= end_turn_button_x && mouse_x <= end_turn_button_x + END_TURN_BUTTON_WIDTH * SCALE &&
-        mouse_y >= button_y && mouse_y <= button_y + END_TURN_BUTTON_HEIGHT * SCALE;
-    let end_turn_button_color = if end_turn_button_enabled {
-        if is_end_turn_hover { Color::new(0.1, 0.3, 0.7, 0.9) } else { Color::new(0.2, 0.4, 0.8, 0.9) }
-    } else {
-        Color::new(0.5, 0.5, 0.5, 0.7)
+    let stones = match config.stones_per_turn {
+        StonesPerTurn::Fixed(1) => strings.stones_one.to_owned(),
+        StonesPerTurn::Fixed(count) => fill(strings.stones_n, &[&count]),
+        StonesPerTurn::Connect6 => strings.stones_connect6.to_owned(),
     };
-    let end_turn_scale = if is_end_turn_hover { HOVER_SCALE } else { 1.0 };
-    let end_turn_draw_x = end_turn_button_x - (END_TURN_BUTTON_WIDTH * SCALE * (end_turn_scale - 1.0)) / 2.0;
-    let end_turn_draw_y = button_y - (END_TURN_BUTTON_HEIGHT * SCALE * (end_turn_scale - 1.0)) / 2.0;
-
-    let end_turn_button_width = END_TURN_BUTTON_WIDTH * SCALE;
-    let end_turn_button_height = END_TURN_BUTTON_HEIGHT * SCALE;
-    let end_turn_text = "End Turn";
-    let end_turn_text_size = 22.0 * SCALE;
-
-    draw_rectangle(
-        end_turn_draw_x,
-        end_turn_draw_y,
-        end_turn_button_width * end_turn_scale,
-        end_turn_button_height * end_turn_scale,
-        end_turn_button_color,
-    );
-    let end_turn_text_width = measure_text(end_turn_text, None, end_turn_text_size as u16, 1.0).width;
-    draw_text(
-        end_turn_text,
-        end_turn_draw_x + (end_turn_button_width * end_turn_scale - end_turn_text_width) / 2.0,
-        end_turn_draw_y + 30.0 * SCALE,
-        end_turn_text_size,
-        WHITE,
-    );
-}
-
-// 绘制游戏规则说明
-fn draw_game_rules() {
-    let base_y = BOARD_OFFSET_Y + CELL_SIZE * (BOARD_SIZE - 1) as f32 + 20.0 * SCALE;
-    let window_center_x = WINDOW_WIDTH / 2.0;
-
-    let title = "Game Rules";
-    let title_size = 26.0 * SCALE;
-    let rule_lines = [
-        "1. Black goes first. Players take turns, 1 piece per turn.",
-        "2. Black's pieces: 90% Black / 70% Black (rotates each turn)",
-        "3. White's pieces: 90% White / 70% White (rotates each turn)",
-        "4. Click 'Preview Board' to see final pieces once per turn.",
-        "5. Win by getting 5 same pieces in a row after preview."
+    let win = config.win_condition();
+    let win_text = match win.rule {
+        WinRule::Freestyle => strings.rule_win,
+        WinRule::Exact => strings.rule_win_exact,
+        WinRule::BlackExact => strings.rule_win_black_exact,
+        WinRule::WhiteExact => strings.rule_win_white_exact,
+    };
+    let mut rules = vec![
+        fill(strings.rule_turns, &[&stones]),
+        black_pieces.to_owned(),
+        white_pieces.to_owned(),
+        match config.preview_rule {
+            PreviewRule::PerTurn => fill(strings.rule_preview, &[&previews]),
+            PreviewRule::PerGame => fill(strings.rule_preview_per_game, &[&previews]),
+            PreviewRule::Bank => fill(strings.rule_preview_bank, &[&previews, &PREVIEW_BANK_LIMIT]),
+            PreviewRule::CostsPlacement => fill(strings.rule_preview_costs, &[&previews]),
+        },
+        fill(win_text, &[&win.length]),
     ];
-    let rule_size = 18.0 * SCALE;
-    let line_spacing = 24.0 * SCALE;
-    let padding = 25.0 * SCALE;
-    let side_margin = 50.0 * SCALE;
-
-    let title_width = measure_text(title, None, title_size as u16, 1.0).width;
-    let mut max_rule_width = 0.0;
-    for line in &rule_lines {
-        let width = measure_text(line, None, rule_size as u16, 1.0).width;
-        if width > max_rule_width {
-            max_rule_width = width;
+    match config.double_win {
+        DoubleWinRule::Draw => {}
+        DoubleWinRule::ObserverLoses => rules.push(strings.rule_double_win_observer_loses.to_owned()),
+        DoubleWinRule::ObserverWins => rules.push(strings.rule_double_win_observer_wins.to_owned()),
+        DoubleWinRule::CountLines => rules.push(strings.rule_double_win_count_lines.to_owned()),
+        DoubleWinRule::Reroll => rules.push(strings.rule_double_win_reroll.to_owned()),
+    }
+    if config.renju_active() {
+        rules.push(strings.rule_renju.to_owned());
+    }
+    match config.entangle {
+        EntangleRule::Off => {}
+        EntangleRule::Opposite => rules.push(strings.rule_entangle_opposite.to_owned()),
+        EntangleRule::Same => rules.push(strings.rule_entangle_same.to_owned()),
+    }
+    if config.superposition {
+        rules.push(strings.rule_superposition.to_owned());
+    }
+    if config.power_cards > 0 {
+        rules.push(strings.rule_power_cards.to_owned());
+    }
+    match config.drift {
+        Drift::Off => {}
+        Drift::Even(step) => rules.push(fill(strings.rule_drift_even, &[&step])),
+        Drift::Owner(step) => rules.push(fill(strings.rule_drift_owner, &[&step])),
+    }
+    match (config.time_minutes, config.time_bonus) {
+        (0, TimeBonus::None | TimeBonus::Fischer(_)) => {}
+        (minutes, TimeBonus::None) => rules.push(fill(strings.rule_time, &[&minutes])),
+        (minutes, TimeBonus::Fischer(seconds)) => rules.push(fill(strings.rule_time_fischer, &[&minutes, &seconds])),
+        (minutes, TimeBonus::ByoYomi(periods, seconds)) => {
+            rules.push(fill(strings.rule_time_byo_yomi, &[&minutes, &seconds, &periods]))
         }
     }
-    let content_max_width = title_width.max(max_rule_width);
-    let bg_width = (content_max_width + 2.0 * padding).min(WINDOW_WIDTH - 2.0 * side_margin);
+    match config.opening {
+        OpeningRule::Standard => {}
+        OpeningRule::Swap => rules.push(strings.rule_opening_swap.to_owned()),
+        OpeningRule::Swap2 => rules.push(strings.rule_opening_swap2.to_owned()),
+        OpeningRule::ProbSwap => rules.push(strings.rule_opening_prob_swap.to_owned()),
+    }
+    rules.push(strings.rule_keys.to_owned());
+    rules
+}
 
+// 绘制游戏规则说明：规则按面板宽度折行（续行与编号后的文字对齐），
+// 行数多到超出窗口底部时逐步缩小字号
+fn draw_game_rules(layout: &Layout, theme: &Theme, strings: &Strings, config: &GameConfig) {
+    const SHRINK_STEPS: [f32; 6] = [1.0, 0.9, 0.8, 0.7, 0.6, 0.5];
+    let scale = layout.scale;
+    let base_y = layout.board_bottom() + 20.0 * scale;
+    let window_center_x = layout.center_x();
+
+    let title = strings.rules_title;
+    let title_size = 26.0 * scale;
+    let rules = game_rule_lines(strings, config);
+    let padding = 25.0 * scale;
+    let side_margin = 50.0 * scale;
+    let bg_width = layout.width - 2.0 * side_margin;
+    let text_width = bg_width - 2.0 * padding;
     let title_height = title_size * 1.2;
-    let rules_total_height = (rule_lines.len() as f32) * line_spacing;
-    let bg_height = title_height + rules_total_height + 10.0 * SCALE;
-
+    let bg_y = base_y - 5.0 * scale;
+    let available = layout.bottom() - 5.0 * scale - bg_y;
+
+    // 每行：编号（只在首行）、缩进与文字
+    let layout_rules = |shrink: f32| {
+        let rule_size = 18.0 * scale * shrink;
+        let mut lines = Vec::new();
+        for (index, rule) in rules.iter().enumerate() {
+            let number = format!("{}. ", index + 1);
+            let indent = measure_ui_text(&number, rule_size).width;
+            for (part, text) in wrap_ui_text(rule, rule_size, text_width - indent).into_iter().enumerate() {
+                lines.push(((part == 0).then(|| number.clone()), indent, text));
+            }
+        }
+        (rule_size, 24.0 * scale * shrink, lines)
+    };
+    let (rule_size, line_spacing, lines) = SHRINK_STEPS
+        .iter()
+        .map(|&shrink| layout_rules(shrink))
+        .find(|(_, spacing, lines)| title_height + lines.len() as f32 * spacing + 10.0 * scale <= available)
+        .unwrap_or_else(|| layout_rules(SHRINK_STEPS[SHRINK_STEPS.len() - 1]));
+    let bg_height = title_height + lines.len() as f32 * line_spacing + 10.0 * scale;
     let bg_x = window_center_x - bg_width / 2.0;
-    let bg_y = base_y - 5.0 * SCALE;
 
     draw_rectangle(
         bg_x,
         bg_y,
         bg_width,
         bg_height,
-        Color::new(0.95, 0.95, 0.95, 0.9),
+        theme.rules_panel,
     );
 
+    let title_width = measure_ui_text(title, title_size).width;
     let title_x = window_center_x - title_width / 2.0;
     let title_draw_y = bg_y + padding;
-    draw_text(
+    draw_ui_text(
         title,
         title_x,
         title_draw_y,
         title_size,
-        Color::new(0.8, 0.2, 0.2, 1.0),
+        theme.rules_title,
     );
 
-    let rules_start_y = title_draw_y + line_spacing * 1.0;
-    let rule_x = window_center_x - max_rule_width / 2.0 + 5.0 * SCALE;
-
-    for (i, line) in rule_lines.iter().enumerate() {
+    let rules_start_y = title_draw_y + line_spacing;
+    let rule_x = bg_x + padding;
+    for (i, (number, indent, text)) in lines.iter().enumerate() {
         let y = rules_start_y + (i as f32) * line_spacing;
-        draw_text(
-            line,
-            rule_x,
+        if let Some(number) = number {
+            draw_ui_text(number, rule_x, y, rule_size, theme.text);
+        }
+        draw_ui_text(
+            text,
+            rule_x + indent,
             y,
             rule_size,
-            BLACK,
+            theme.text,
+        );
+    }
+}
+
+// 窗口配置：可缩放窗口，启用高DPI渲染
+fn window_conf() -> Conf {
+    Conf {
+        window_title: "Probability Gomoku".to_owned(),
+        window_width: 900,
+        window_height: 1000,
+        window_resizable: true,
+        high_dpi: true,
+        ..Default::default()
+    }
+}
+
+// 获胜横幅是否已出现（坍缩动画播放到横幅阶段后才出现）
+fn banner_visible(state: &GameState) -> bool {
+    state.game_over
+        && state.observation_winner.is_some()
+        && state.collapse.is_none_or(|collapse| collapse.banner() > 0.0)
+}
+
+// 对局中的一帧：处理输入、更新状态、渲染画面，返回需要切换到的界面
+fn play_frame(game_state: &mut GameState, settings: &Settings, theme: &Theme, sounds: &Sounds) -> Option<Screen> {
+    // 根据当前窗口尺寸计算本帧布局
+    let layout = Layout::new(game_state.config.board_size);
+    let strings = i18n::strings(settings.language);
+    let player_before = game_state.current_player;
+    let was_over = game_state.game_over;
+    let was_observing = game_state.show_observation;
+    let moves_before = game_state.moves.len();
+    let cards_before = game_state.cards_played.len();
+    let choices_before = game_state.opening_choices.len();
+    let banner_was_visible = banner_visible(game_state);
+    let mut next_screen = None;
+
+    // 处理按钮点击
+    let buttons = game_buttons(&layout, strings, game_state);
+    // 对局中没有重新开始按钮，按 R 键重新开始
+    let triggered = triggered_action(&buttons)
+        .or_else(|| (!game_state.game_over && is_key_pressed(KeyCode::R)).then_some(UiAction::Restart));
+    if triggered.is_some() {
+        sounds.play(SoundEffect::Click, settings);
+    }
+    // 按下其他按钮即取消待确认的重新开始
+    if triggered.is_some_and(|action| action != UiAction::Restart) {
+        game_state.restart_pending = false;
+    }
+    match triggered {
+        // 点击预览棋盘按钮
+        Some(UiAction::TogglePreview) => {
+            if game_state.show_observation {
+                game_state.show_observation = false;
+            } else {
+                observe_board(game_state);
+            }
+        }
+        // 点击结束回合按钮：开启确认时需要再按一次
+        Some(UiAction::EndTurn) => {
+            if settings.confirm_end_turn && !game_state.end_turn_pending {
+                game_state.end_turn_pending = true;
+            } else {
+                end_turn(game_state);
+            }
+        }
+        // 交换开局中选择颜色或再摆两子
+        Some(UiAction::ChooseBlack) => {
+            apply_opening_choice(game_state, OpeningChoice::Black);
+        }
+        Some(UiAction::ChooseWhite) => {
+            apply_opening_choice(game_state, OpeningChoice::White);
+        }
+        Some(UiAction::ChooseExtend) => {
+            apply_opening_choice(game_state, OpeningChoice::Extend);
+        }
+        // 开始或取消两点落子
+        Some(UiAction::ToggleEntangle) => toggle_pair_move(game_state, PairMove::Entangle),
+        Some(UiAction::ToggleSuperpose) => toggle_pair_move(game_state, PairMove::Superpose),
+        // 选中或取消能力卡，再点击目标棋子使用
+        Some(UiAction::SelectCard(card)) => toggle_card(game_state, card),
+        // 暂停或继续计时
+        Some(UiAction::TogglePause) => game_state.paused = !game_state.paused,
+        // 点击重启游戏：沿用本局设置；进行中的对局需再按一次确认，重新开始后丢弃旧的自动存档
+        Some(UiAction::Restart) => {
+            if !game_state.game_over && !game_state.moves.is_empty() && !game_state.restart_pending {
+                game_state.restart_pending = true;
+            } else {
+                record::clear_saved_game();
+                *game_state = GameState::new(game_state.config);
+            }
+        }
+        // 返回主菜单：未结束的对局留待继续
+        Some(UiAction::Menu) => {
+            if !game_state.game_over && !game_state.moves.is_empty() {
+                if let Err(err) = record::save_game(&game_state.to_record()) {
+                    eprintln!("Failed to save game: {}", err);
+                }
+            }
+            next_screen = Some(Screen::MainMenu);
+        }
+        // 点击退出游戏
+        Some(UiAction::Exit) => std::process::exit(0),
+        _ => {}
+    }
+
+    // 回车跳过正在播放的坍缩动画
+    let skipped = game_state.collapse.is_some() && (is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter));
+    if skipped {
+        game_state.collapse = None;
+    }
+
+    // 鼠标落子（本帧未触发按钮时）
+    if triggered.is_none() && !game_state.paused && !game_state.is_computer_turn() &&
+       is_mouse_button_pressed(MouseButton::Left) {
+        let (mouse_x, mouse_y) = mouse_position();
+        if let Some((row, col)) = layout.mouse_to_grid(mouse_x, mouse_y) {
+            if place_at(game_state, row, col) {
+                game_state.cursor = (row, col);
+                game_state.cursor_visible = false;
+            }
+        }
+    }
+
+    // 键盘操作：方向键/WASD移动光标，回车在光标处落子
+    if !skipped {
+        handle_cursor_keys(game_state);
+    }
+    update_computer_turn(game_state);
+    update_clock(game_state);
+    // 落子后取消待确认的重新开始
+    if game_state.moves.len() != moves_before {
+        game_state.restart_pending = false;
+    }
+
+    // 预览开始时播放坍缩动画，预览关闭或播放完毕后清除
+    if game_state.show_observation && !was_observing {
+        game_state.collapse = settings.animation_speed.factor().map(CollapseAnimation::new);
+    } else if let Some(collapse) = game_state.collapse.as_mut().filter(|_| !game_state.paused) {
+        collapse.update(get_frame_time());
+    }
+    if !game_state.show_observation || game_state.collapse.is_some_and(|collapse| collapse.finished()) {
+        game_state.collapse = None;
+    }
+
+    // 音效：落子或使用能力卡、预览坍缩、获胜横幅出现
+    if game_state.moves.len() > moves_before || game_state.cards_played.len() > cards_before {
+        sounds.play(SoundEffect::Place, settings);
+    }
+    if game_state.show_observation && !was_observing {
+        sounds.play(SoundEffect::Collapse, settings);
+    }
+    if banner_visible(game_state) && !banner_was_visible {
+        sounds.play(SoundEffect::Win, settings);
+    }
+
+    // 回合结束时自动保存，对局结束时保存复盘记录
+    if game_state.game_over && !was_over {
+        if let Err(err) = record::save_finished_game(&game_state.to_record()) {
+            eprintln!("Failed to save game record: {}", err);
+        }
+        record::clear_saved_game();
+    } else if (game_state.current_player != player_before || game_state.opening_choices.len() != choices_before) &&
+              !game_state.game_over {
+        if let Err(err) = record::save_game(&game_state.to_record()) {
+            eprintln!("Failed to save game: {}", err);
+        }
+    }
+
+    // 渲染画面（按钮按更新后的状态重新构建）
+    let buttons = game_buttons(&layout, strings, game_state);
+    clear_background(theme.background);
+    draw_board(&layout, theme, settings.show_coordinates);
+    // 暂停时隐藏棋子，避免停表思考
+    if !game_state.paused {
+        draw_entanglements(&layout, theme, &game_state.correlations.entanglements);
+        draw_prob_pieces(&layout, theme, settings, &game_state.board);
+        refresh_forbidden_points(game_state);
+        draw_piece_preview(&layout, theme, game_state);
+        draw_keyboard_cursor(&layout, theme, game_state);
+    }
+    draw_ui(&layout, theme, settings, game_state, &buttons);
+
+    // 暂停时预览棋盘同样隐藏
+    if game_state.show_observation && !game_state.paused {
+        draw_observation_board(
+            &layout,
+            theme,
+            &game_state.observation_board,
+            &game_state.winning_pieces,
+            game_state.collapse.as_ref(),
+        );
+    }
+    if let Some(outcome) = game_state.observation_winner.filter(|_| !game_state.paused) {
+        let slide = game_state.collapse.map_or(1.0, |collapse| collapse.banner());
+        draw_winner_banner(&layout, theme, outcome.text(strings), slide);
+    }
+    if game_state.collapse.is_some() && !game_state.paused {
+        let hint_size = 20.0 * layout.scale;
+        let hint_width = measure_ui_text(strings.skip_animation, hint_size).width;
+        draw_ui_text(
+            strings.skip_animation,
+            layout.center_x() - hint_width / 2.0,
+            layout.board_y - layout.piece_radius - 6.0 * layout.scale,
+            hint_size,
+            theme.text,
         );
     }
+
+    if !game_state.game_over {
+        draw_game_rules(&layout, theme, strings, &game_state.config);
+    }
+
+    draw_buttons(&buttons);
+    if !game_state.show_observation && !game_state.paused {
+        tooltip::draw_stone_tooltip(
+            &layout,
+            theme,
+            strings,
+            &game_state.board,
+            &game_state.correlations,
+            &game_state.moves,
+            game_state.config.win_condition(),
+        );
+    }
+    next_screen
+}
+
+// 复盘中的一帧：逐步查看上一局的落子
+fn replay_frame(replay: &mut Replay, settings: &Settings, theme: &Theme, sounds: &Sounds) -> Option<Screen> {
+    let layout = Layout::new(replay.record.config.board_size);
+    let strings = i18n::strings(settings.language);
+    let buttons = replay::replay_buttons(&layout, strings, replay);
+    let mut next_screen = None;
+    let triggered = triggered_action(&buttons);
+    match triggered {
+        Some(UiAction::ReplayPrev) => replay.step_back(),
+        Some(UiAction::ReplayNext) => {
+            replay.step_forward();
+            sounds.play(SoundEffect::Place, settings);
+        }
+        Some(UiAction::Back) => next_screen = Some(Screen::MainMenu),
+        _ => {}
+    }
+    if triggered.is_some_and(|action| action != UiAction::ReplayNext) {
+        sounds.play(SoundEffect::Click, settings);
+    }
+
+    let buttons = replay::replay_buttons(&layout, strings, replay);
+    clear_background(theme.background);
+    draw_board(&layout, theme, settings.show_coordinates);
+    let state = replay.state();
+    draw_prob_pieces(&layout, theme, settings, &state.board);
+    replay::draw_replay_info(&layout, theme, strings, replay);
+    draw_buttons(&buttons);
+    tooltip::draw_stone_tooltip(
+        &layout,
+        theme,
+        strings,
+        &state.board,
+        &state.correlations,
+        &replay.record.moves[..replay.step],
+        replay.record.config.win_condition(),
+    );
+    next_screen
+}
+
+// 程序入口：带 --bench 参数时只运行基准测试，否则打开游戏窗口
+fn main() {
+    if std::env::args().any(|arg| arg == "--bench") {
+        bench::run();
+        return;
+    }
+    macroquad::Window::from_config(window_conf(), run());
 }
 
-// 游戏主函数：初始化窗口、处理输入、更新状态、渲染画面
-#[macroquad::main("Probability Gomoku")]
-async fn main() {
-    miniquad::window::set_window_size(1200, 1300);
-    let mut game_state = GameState::default();
+// 游戏主循环：按当前界面分派输入处理与渲染
+async fn run() {
+    let mut settings = Settings::load();
+    let mut theme = theme::find_theme(&settings.theme);
+    text::update_ui_font(settings.language, settings.font_path.as_deref());
+    let mut screen = Screen::MainMenu;
+    let mut new_game_config = GameConfig::default();
+    let mut game_state = GameState::new(new_game_config);
+    let mut replay: Option<Replay> = None;
+    let mut menu_focus = 0;
+    let sounds = Sounds::load().await;
 
     loop {
-        // 处理鼠标左键点击输入（游戏未结束时）
-        if !game_state.game_over && is_mouse_button_pressed(MouseButton::Left) {
-            let (mouse_x, mouse_y) = mouse_position();
-
-            let button_y = 160.0 * SCALE;
-            let button_width = 160.0 * SCALE;
-            let button_height = 50.0 * SCALE;
-            let observe_x = BOARD_OFFSET_X + (BOARD_ACTUAL_WIDTH - button_width - END_TURN_BUTTON_WIDTH * SCALE - 40.0 * SCALE) / 2.0;
-            let end_turn_button_x = observe_x + button_width + 40.0 * SCALE;
-
-            // 点击预览棋盘按钮
-            if mouse_x >= observe_x && mouse_x <= observe_x + button_width &&
-               mouse_y >= button_y && mouse_y <= button_y + button_height {
-                if game_state.show_observation {
-                    game_state.show_observation = false;
-                } else if game_state.observe_remaining > 0 {
-                    game_state.observe_remaining -= 1;
-                    let mut observation_board = vec![vec![DefinitePiece::Empty; BOARD_SIZE]; BOARD_SIZE];
-                    for row in 0..BOARD_SIZE {
-                        for col in 0..BOARD_SIZE {
-                            observation_board[row][col] = prob_to_definite(game_state.board[row][col]);
-                        }
+        let strings = i18n::strings(settings.language);
+        let next_screen = match screen {
+            Screen::MainMenu => {
+                let layout = Layout::new(new_game_config.board_size);
+                let mut buttons = menu::main_menu_buttons(
+                    &layout,
+                    strings,
+                    record::has_saved_game(),
+                    record::has_finished_game(),
+                );
+                let action = navigate_focus(&mut buttons, &mut menu_focus);
+                if action.is_some() {
+                    sounds.play(SoundEffect::Click, &settings);
+                }
+
+                clear_background(theme.background);
+                menu::draw_menu_title(&layout, &theme, strings.game_title);
+                draw_buttons(&buttons);
+
+                match action {
+                    Some(UiAction::NewGame) => Some(Screen::NewGame),
+                    Some(UiAction::Settings) => Some(Screen::Settings),
+                    Some(UiAction::LoadGame) => record::load_saved_game().map(|saved| {
+                        game_state = GameState::from_record(&saved);
+                        Screen::Playing
+                    }),
+                    Some(UiAction::Replay) => record::load_finished_game().map(|finished| {
+                        replay = Some(Replay::new(finished));
+                        Screen::Replay
+                    }),
+                    Some(UiAction::Exit) => std::process::exit(0),
+                    _ => None,
+                }
+            }
+            Screen::NewGame => {
+                let layout = Layout::new(new_game_config.board_size);
+                let mut buttons = menu::new_game_buttons(&layout, strings, &new_game_config);
+                let action = navigate_focus(&mut buttons, &mut menu_focus);
+                if action.is_some() {
+                    sounds.play(SoundEffect::Click, &settings);
+                }
+
+                // 左右方向键切换焦点选项的取值
+                let focused = buttons[menu_focus].action;
+                if is_key_pressed(KeyCode::Left) || is_key_pressed(KeyCode::A) {
+                    menu::cycle_option(&mut new_game_config, focused, -1);
+                }
+                if is_key_pressed(KeyCode::Right) || is_key_pressed(KeyCode::D) {
+                    menu::cycle_option(&mut new_game_config, focused, 1);
+                }
+
+                clear_background(theme.background);
+                menu::draw_menu_title(&layout, &theme, strings.new_game);
+                draw_buttons(&buttons);
+
+                match action {
+                    Some(UiAction::StartGame) => {
+                        game_state = GameState::new(new_game_config);
+                        Some(Screen::Playing)
                     }
-                    game_state.observation_board = observation_board;
-                    let (winner, winning_pieces) = check_winner(&game_state.observation_board);
-                    game_state.observation_winner = winner;
-                    game_state.winning_pieces = winning_pieces;
-                    game_state.show_observation = true;
-
-                    if winner.is_some() {
-                        game_state.game_over = true;
+                    Some(UiAction::Back) => Some(Screen::MainMenu),
+                    Some(option) => {
+                        menu::cycle_option(&mut new_game_config, option, 1);
+                        None
                     }
+                    None => None,
                 }
             }
-
-            // 点击结束回合按钮
-            if game_state.current_turn_move_count > 0 &&
-               mouse_x >= end_turn_button_x && mouse_x <= end_turn_button_x + END_TURN_BUTTON_WIDTH * SCALE &&
-               mouse_y >= button_y && mouse_y <= button_y + END_TURN_BUTTON_HEIGHT * SCALE {
-                switch_player_prob(&mut game_state);
-                game_state.current_player = match game_state.current_player {
-                    Player::Black => Player::White,
-                    Player::White => Player::Black,
+            Screen::Settings => {
+                let layout = Layout::new(new_game_config.board_size);
+                let mut buttons = menu::settings_buttons(&layout, strings, &settings);
+                let action = navigate_focus(&mut buttons, &mut menu_focus);
+
+                // 左右方向键切换焦点设置项的取值，回车或点击向后切换
+                let focused = buttons[menu_focus].action;
+                let change = if is_key_pressed(KeyCode::Left) || is_key_pressed(KeyCode::A) {
+                    Some((focused, -1))
+                } else if is_key_pressed(KeyCode::Right) || is_key_pressed(KeyCode::D) {
+                    Some((focused, 1))
+                } else {
+                    action.map(|action| (action, 1))
                 };
-                game_state.observe_remaining = 1;
-                game_state.show_observation = false;
-                game_state.observation_winner = None;
-                game_state.winning_pieces = WinningPieces::default();
-                game_state.current_turn_move_count = 0;
-                game_state.show_prob_hint = true;
-            }
+                if let Some((action, step)) = change {
+                    let changed = menu::change_setting(&mut settings, action, step);
+                    if changed || action == UiAction::Back {
+                        sounds.play(SoundEffect::Click, &settings);
+                    }
+                    if changed {
+                        theme = theme::find_theme(&settings.theme);
+                        text::update_ui_font(settings.language, settings.font_path.as_deref());
+                        if let Err(err) = settings.save() {
+                            eprintln!("Failed to save settings: {}", err);
+                        }
+                    }
+                }
 
-            // 落子操作
-            if !game_state.show_observation && game_state.current_turn_move_count == 0 {
-                if let Some((row, col)) = mouse_to_grid(mouse_x, mouse_y) {
-                    if game_state.board[row][col] == ProbPiece::Empty {
-                        let current_piece = get_current_prob_piece(&game_state);
-                        game_state.board[row][col] = current_piece;
+                clear_background(theme.background);
+                menu::draw_menu_title(&layout, &theme, strings.settings);
+                draw_buttons(&buttons);
 
-                        game_state.current_turn_move_count = 1;
-                        game_state.show_prob_hint = false;
-                    }
+                match action {
+                    Some(UiAction::Back) => Some(Screen::MainMenu),
+                    _ => None,
                 }
             }
+            Screen::Playing => play_frame(&mut game_state, &settings, &theme, &sounds),
+            Screen::Replay => match replay.as_mut() {
+                Some(replay) => replay_frame(replay, &settings, &theme, &sounds),
+                None => Some(Screen::MainMenu),
+            },
+        };
+
+        if let Some(next) = next_screen {
+            screen = next;
+            menu_focus = 0;
         }
 
-        // 处理游戏结束后的按钮点击（重启/退出）
-        if game_state.game_over && is_mouse_button_pressed(MouseButton::Left) {
-            let (mouse_x, mouse_y) = mouse_position();
+        next_frame().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn main_time_overflow_goes_to_byo_yomi() {
+        let config = GameConfig { time_minutes: 1, time_bonus: TimeBonus::ByoYomi(2, 10), ..GameConfig::default() };
+        let mut state = GameState::new(config);
+        state.clocks[0] = 0.5;
+        // 主用时剩0.5秒，一帧走过2秒：多出的1.5秒从读秒中扣除
+        tick_clock(&mut state, 2.0);
+        assert_eq!(state.clocks[0], 0.0);
+        assert_eq!(state.periods[0], 2);
+        assert!((state.period_clock - 8.5).abs() < 1e-4);
+        // 读秒用完时多出的时间计入下一次读秒
+        tick_clock(&mut state, 9.0);
+        assert_eq!(state.periods[0], 1);
+        assert!((state.period_clock - 9.5).abs() < 1e-4);
+        assert!(!state.game_over);
+        tick_clock(&mut state, 9.5);
+        assert!(state.game_over);
+        assert_eq!(state.observation_winner, Some(Outcome::Timeout(Player::Black)));
+    }
+
+    // 依次落子并结束回合
+    fn play_turns(state: &mut GameState, points: &[(usize, usize)]) {
+        for &(row, col) in points {
+            assert!(place_piece(state, row, col));
+            end_turn(state);
+        }
+    }
 
-            let button_y = BOARD_OFFSET_Y + CELL_SIZE * BOARD_SIZE as f32 + 40.0 * SCALE;
-            let button_width = 180.0 * SCALE * GAME_OVER_BUTTON_SCALE;
-            let button_height = 60.0 * SCALE * GAME_OVER_BUTTON_SCALE;
-            let restart_x = BOARD_OFFSET_X + (BOARD_ACTUAL_WIDTH - button_width * 2.0 - 60.0 * SCALE) / 2.0;
-            let exit_x = restart_x + button_width + 60.0 * SCALE;
+    #[test]
+    fn drift_moves_only_settled_stones() {
+        let config = GameConfig { tiers: ProbTiers::StrongOnly, drift: Drift::Even(5), ..GameConfig::default() };
+        let mut state = GameState::new(config);
+        // 本回合刚落下的棋子不漂移
+        play_turns(&mut state, &[(7, 7)]);
+        assert_eq!(state.board[7][7], ProbPiece::BLACK_90);
+        play_turns(&mut state, &[(7, 8)]);
+        assert_eq!(state.board[7][7], ProbPiece::Stone(85));
+        assert_eq!(state.board[7][8], ProbPiece::BLACK_10);
+        play_turns(&mut state, &[(7, 9)]);
+        assert_eq!(state.board[7][7], ProbPiece::Stone(80));
+        assert_eq!(state.board[7][8], ProbPiece::Stone(15));
+    }
 
-            // 点击重启游戏
-            if mouse_x >= restart_x && mouse_x <= restart_x + button_width &&
-               mouse_y >= button_y && mouse_y <= button_y + button_height {
-                game_state = GameState::default();
-            }
+    #[test]
+    fn drift_toward_owner_stops_at_certainty() {
+        let config = GameConfig { tiers: ProbTiers::StrongOnly, drift: Drift::Owner(8), ..GameConfig::default() };
+        let mut state = GameState::new(config);
+        play_turns(&mut state, &[(7, 7), (7, 8)]);
+        assert_eq!(state.board[7][7], ProbPiece::Stone(98));
+        play_turns(&mut state, &[(7, 9)]);
+        assert_eq!(state.board[7][7], ProbPiece::Stone(100));
+        assert_eq!(state.board[7][8], ProbPiece::Stone(2));
+    }
 
-            // 点击退出游戏
-            if mouse_x >= exit_x && mouse_x <= exit_x + button_width &&
-               mouse_y >= button_y && mouse_y <= button_y + button_height {
-                std::process::exit(0);
+    #[test]
+    fn drift_skips_replaced_and_observed_points() {
+        let config = GameConfig { tiers: ProbTiers::StrongOnly, drift: Drift::Even(5), ..GameConfig::default() };
+        let mut state = GameState::new(config);
+        play_turns(&mut state, &[(7, 7), (7, 8)]);
+        // 模拟移除卡清空 (7,7) 后黑方在同一点重新落子：只按最后一手漂移一次
+        set_point(&mut state, (7, 7), ProbPiece::Empty);
+        state.observed.push((7, 8));
+        play_turns(&mut state, &[(7, 7), (7, 9)]);
+        assert_eq!(state.board[7][7], ProbPiece::Stone(85));
+        assert_eq!(state.board[7][8], ProbPiece::BLACK_10);
+    }
+
+    // 由字符画构造确定棋盘：X 黑、O 白、. 空
+    fn definite_board(rows: &[&str]) -> Vec<Vec<DefinitePiece>> {
+        rows.iter()
+            .map(|row| {
+                row.chars()
+                    .map(|c| match c {
+                        'X' => DefinitePiece::Black,
+                        'O' => DefinitePiece::White,
+                        _ => DefinitePiece::Empty,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    const FIVE: WinCondition = WinCondition { length: 5, rule: WinRule::Freestyle };
+
+    #[test]
+    fn overline_is_recorded_in_full() {
+        let board = definite_board(&[
+            "........",
+            "XXXXXX..",
+            "........",
+            "........",
+            "........",
+            "........",
+            "........",
+            "........",
+        ]);
+        let (outcome, pieces) = check_winner(&board, FIVE);
+        assert_eq!(outcome, Some(Outcome::Win(Player::Black)));
+        assert_eq!(pieces.black, vec![(0..6).map(|col| (1, col)).collect::<Vec<_>>()]);
+        assert!(pieces.white.is_empty());
+    }
+
+    #[test]
+    fn every_winning_line_is_recorded() {
+        let board = definite_board(&[
+            "XXXXX...",
+            "X.......",
+            "X.....O.",
+            "X....O..",
+            "X...O...",
+            "...O....",
+            "..O.....",
+            "........",
+        ]);
+        let (outcome, pieces) = check_winner(&board, FIVE);
+        assert_eq!(outcome, Some(Outcome::BothWin));
+        assert_eq!(pieces.black.len(), 2);
+        assert!(pieces.black.iter().all(|line| line.len() == 5 && line.contains(&(0, 0))));
+        assert_eq!(pieces.white, vec![vec![(2, 6), (3, 5), (4, 4), (5, 3), (6, 2)]]);
+    }
+
+    #[test]
+    fn four_is_not_a_win() {
+        let board = definite_board(&["XXXX.", "OOOO.", ".....", ".....", "....."]);
+        assert_eq!(check_winner(&board, FIVE), (None, WinningPieces::default()));
+        let full = definite_board(&["XXXXO", "OOOOX", "XXXXO", "OOOOX", "XXXXO"]);
+        assert_eq!(check_winner(&full, FIVE).0, Some(Outcome::BoardFull));
+    }
+
+    #[test]
+    fn exact_five_applies_per_colour() {
+        let board = definite_board(&[
+            "XXXXXX..",
+            "........",
+            "OOOOOO..",
+            "........",
+            "XXXXX...",
+            "........",
+            "........",
+            "........",
+        ]);
+        let rule = |rule| check_winner(&board, WinCondition { length: 5, rule });
+        // 黑方有长连也有恰好五连，白方只有长连
+        let (outcome, pieces) = rule(WinRule::Exact);
+        assert_eq!(outcome, Some(Outcome::Win(Player::Black)));
+        assert_eq!(pieces.black, vec![(0..5).map(|col| (4, col)).collect::<Vec<_>>()]);
+        let (outcome, pieces) = rule(WinRule::BlackExact);
+        assert_eq!(outcome, Some(Outcome::BothWin));
+        assert_eq!(pieces.black.len(), 1);
+        assert_eq!(pieces.white[0].len(), 6);
+        let (outcome, pieces) = rule(WinRule::WhiteExact);
+        assert_eq!(outcome, Some(Outcome::Win(Player::Black)));
+        assert_eq!(pieces.black.len(), 2);
+        assert_eq!(rule(WinRule::Freestyle).0, Some(Outcome::BothWin));
+    }
+
+    fn opening_config(opening: OpeningRule) -> GameConfig {
+        GameConfig { tiers: ProbTiers::StrongOnly, opening, ..GameConfig::default() }
+    }
+
+    #[test]
+    fn swap_lets_second_player_take_black() {
+        let mut state = GameState::new(opening_config(OpeningRule::Swap));
+        assert!(!apply_opening_choice(&mut state, OpeningChoice::Black));
+        play_turns(&mut state, &[(7, 7), (7, 8), (8, 8)]);
+        assert_eq!(state.opening, OpeningStage::SecondChooses);
+        assert!(!place_piece(&mut state, 0, 0));
+        // Swap 不能再摆两子
+        assert!(!apply_opening_choice(&mut state, OpeningChoice::Extend));
+        assert!(apply_opening_choice(&mut state, OpeningChoice::Black));
+        assert_eq!(state.opening, OpeningStage::Done);
+        assert!(state.swapped);
+        // 轮到白棋，由执白的先手方落子
+        assert_eq!(state.current_player, Player::White);
+        assert_eq!(state.acting_seat(), Player::Black);
+        assert_eq!(state.opening_choices, vec![OpeningChoice::Black]);
+    }
+
+    #[test]
+    fn swap2_extends_then_first_player_chooses() {
+        let mut state = GameState::new(opening_config(OpeningRule::Swap2));
+        play_turns(&mut state, &[(7, 7), (7, 8), (8, 8)]);
+        assert!(apply_opening_choice(&mut state, OpeningChoice::Extend));
+        assert_eq!(state.opening, OpeningStage::Extending);
+        assert_eq!(state.acting_seat(), Player::White);
+        play_turns(&mut state, &[(8, 7), (6, 6)]);
+        assert_eq!(state.opening, OpeningStage::FirstChooses);
+        assert_eq!(state.acting_seat(), Player::Black);
+        assert!(!apply_opening_choice(&mut state, OpeningChoice::Extend));
+        assert!(apply_opening_choice(&mut state, OpeningChoice::Black));
+        assert!(!state.swapped);
+        assert_eq!(state.current_player, Player::White);
+        assert_eq!(state.acting_seat(), Player::White);
+        assert_eq!(state.opening_choices, vec![OpeningChoice::Extend, OpeningChoice::Black]);
+    }
+
+    #[test]
+    fn prob_swap_opening_stones_are_weak() {
+        let mut state = GameState::new(opening_config(OpeningRule::ProbSwap));
+        play_turns(&mut state, &[(7, 7), (7, 8), (8, 8)]);
+        assert_eq!(state.board[7][7], ProbPiece::BLACK_70);
+        assert_eq!(state.board[7][8], ProbPiece::BLACK_30);
+        assert_eq!(state.board[8][8], ProbPiece::BLACK_70);
+        // 开局阶段不发放预览，选定颜色后才发放
+        assert_eq!(state.observe_remaining, [0, 0]);
+        assert!(apply_opening_choice(&mut state, OpeningChoice::White));
+        assert!(!state.swapped);
+        assert_eq!(state.previews_left(), 1);
+        assert!(place_piece(&mut state, 6, 6));
+        assert_eq!(state.board[6][6], ProbPiece::BLACK_10);
+    }
+
+    #[test]
+    fn connect6_places_one_then_two_stones() {
+        let config = GameConfig { stones_per_turn: StonesPerTurn::Connect6, ..GameConfig::default() };
+        let mut state = GameState::new(config);
+        assert_eq!(state.stones_this_turn(), 1);
+        assert!(!state.turn_complete());
+        assert!(place_piece(&mut state, 7, 7));
+        assert!(state.turn_complete());
+        assert!(!place_piece(&mut state, 7, 8));
+        end_turn(&mut state);
+        assert_eq!(state.stones_this_turn(), 2);
+        assert!(place_piece(&mut state, 7, 8));
+        // 未落满时结束回合无效
+        end_turn(&mut state);
+        assert_eq!(state.current_player, Player::White);
+        assert!(place_piece(&mut state, 7, 9));
+        end_turn(&mut state);
+        assert_eq!(state.current_player, Player::Black);
+        assert_eq!(state.stones_this_turn(), 2);
+    }
+
+    #[test]
+    fn full_board_completes_the_turn() {
+        let config = GameConfig { board_size: 9, stones_per_turn: StonesPerTurn::Fixed(3), ..GameConfig::default() };
+        let mut state = GameState::new(config);
+        for row in 0..9 {
+            for col in 0..9 {
+                if (row, col) != (0, 0) {
+                    set_point(&mut state, (row, col), ProbPiece::BLACK_90);
+                }
             }
         }
+        assert!(!state.turn_complete());
+        assert!(place_piece(&mut state, 0, 0));
+        assert!(state.turn_complete());
+    }
 
-        // 渲染画面
-        clear_background(WHITE);
-        draw_board();
-        draw_prob_pieces(&game_state.board);
-        draw_piece_preview(&game_state);
-
-        let current_prob_piece = get_current_prob_piece(&game_state);
-        draw_ui(
-            game_state.show_observation,
-            game_state.observe_remaining,
-            game_state.current_player,
-            current_prob_piece,
-            game_state.game_over,
-            game_state.current_turn_move_count,
-            game_state.show_prob_hint,
-        );
+    #[test]
+    fn win_length_is_configurable() {
+        let board = definite_board(&["XXXXX..", "OOOOOO.", ".......", ".......", ".......", ".......", "......."]);
+        let six = |rule| check_winner(&board, WinCondition { length: 6, rule }).0;
+        assert_eq!(six(WinRule::Freestyle), Some(Outcome::Win(Player::White)));
+        assert_eq!(six(WinRule::Exact), Some(Outcome::Win(Player::White)));
+    }
 
-        if game_state.show_observation {
-            draw_observation_board(
-                &game_state.observation_board,
-                game_state.observation_winner,
-                &game_state.winning_pieces,
-            );
+    fn preview_config(preview_rule: PreviewRule, previews: u8) -> GameConfig {
+        GameConfig { preview_rule, previews, ..GameConfig::default() }
+    }
+
+    #[test]
+    fn banked_previews_accumulate_up_to_the_limit() {
+        let mut state = GameState::new(preview_config(PreviewRule::Bank, 2));
+        assert_eq!(state.observe_remaining, [2, 0]);
+        play_turns(&mut state, &[(0, 0), (0, 1)]);
+        assert_eq!(state.observe_remaining, [4, 2]);
+        observe_board(&mut state);
+        assert_eq!(state.previews_left(), 3);
+        state.show_observation = false;
+        let points: Vec<_> = (0..10).map(|col| (2 + col % 2, col)).collect();
+        play_turns(&mut state, &points);
+        assert_eq!(state.observe_remaining, [PREVIEW_BANK_LIMIT, PREVIEW_BANK_LIMIT]);
+        assert_eq!(state.previews_used, vec![2]);
+    }
+
+    #[test]
+    fn per_turn_and_per_game_previews() {
+        let mut state = GameState::new(preview_config(PreviewRule::PerTurn, 1));
+        observe_board(&mut state);
+        assert_eq!(state.previews_left(), 0);
+        assert!(!state.can_preview());
+        state.show_observation = false;
+        play_turns(&mut state, &[(0, 0), (0, 1)]);
+        assert_eq!(state.previews_left(), 1);
+
+        let mut state = GameState::new(preview_config(PreviewRule::PerGame, 1));
+        assert_eq!(state.observe_remaining, [1, 1]);
+        observe_board(&mut state);
+        state.show_observation = false;
+        play_turns(&mut state, &[(0, 0), (0, 1)]);
+        assert_eq!(state.observe_remaining, [0, 1]);
+    }
+
+    #[test]
+    fn preview_can_cost_the_placement() {
+        let mut state = GameState::new(preview_config(PreviewRule::CostsPlacement, 1));
+        observe_board(&mut state);
+        assert!(state.turn_complete());
+        state.show_observation = false;
+        assert!(!place_piece(&mut state, 0, 0));
+        end_turn(&mut state);
+        assert_eq!(state.current_player, Player::White);
+        // 已落子后不能再预览
+        assert!(place_piece(&mut state, 0, 0));
+        assert!(!state.can_preview());
+    }
+
+    #[test]
+    fn double_win_resolution_rules() {
+        let line = |row| (0..5).map(|col| (row, col)).collect::<Vec<_>>();
+        let pieces = WinningPieces { black: vec![line(0), line(1)], white: vec![line(2)] };
+        let resolve = |rule, observer| resolve_double_win(rule, observer, &pieces);
+        assert_eq!(resolve(DoubleWinRule::Draw, Player::Black), Outcome::BothWin);
+        assert_eq!(resolve(DoubleWinRule::ObserverLoses, Player::Black), Outcome::Win(Player::White));
+        assert_eq!(resolve(DoubleWinRule::ObserverWins, Player::White), Outcome::Win(Player::White));
+        assert_eq!(resolve(DoubleWinRule::CountLines, Player::White), Outcome::Win(Player::Black));
+        let even = WinningPieces { black: vec![line(0)], white: vec![line(2)] };
+        assert_eq!(resolve_double_win(DoubleWinRule::CountLines, Player::Black, &even), Outcome::BothWin);
+    }
+
+    // 黑白各有一条必然成立的五连
+    fn certain_double_five(double_win: DoubleWinRule) -> GameState {
+        let mut state = GameState::new(GameConfig { double_win, ..GameConfig::default() });
+        for col in 0..5 {
+            set_point(&mut state, (0, col), ProbPiece::Stone(100));
+            set_point(&mut state, (2, col), ProbPiece::Stone(0));
         }
+        state
+    }
 
-        if !game_state.game_over {
-            draw_game_rules();
-        }
-
-        // 绘制游戏结束后的重启/退出按钮
-        if game_state.game_over {
-            let (mouse_x, mouse_y) = mouse_position();
-            let button_y = BOARD_OFFSET_Y + CELL_SIZE * BOARD_SIZE as f32 + 40.0 * SCALE;
-            let button_width = 180.0 * SCALE * GAME_OVER_BUTTON_SCALE;
-            let button_height = 60.0 * SCALE * GAME_OVER_BUTTON_SCALE;
-            let restart_x = BOARD_OFFSET_X + (BOARD_ACTUAL_WIDTH - button_width * 2.0 - 60.0 * SCALE) / 2.0;
-            let exit_x = restart_x + button_width + 60.0 * SCALE;
-
-            let is_restart_hover = mouse_x >= restart_x && mouse_x <= restart_x + button_width &&
-                mouse_y >= button_y && mouse_y <= button_y + button_height;
-            let restart_color = if is_restart_hover { Color::new(0.1, 0.7, 0.1, 0.9) } else { Color::new(0.0, 0.8, 0.0, 0.9) };
-            let restart_scale = if is_restart_hover { HOVER_SCALE } else { 1.0 };
-            let restart_draw_x = restart_x - (button_width * (restart_scale - 1.0)) / 2.0;
-            let restart_draw_y = button_y - (button_height * (restart_scale - 1.0)) / 2.0;
-
-            draw_rectangle(
-                restart_draw_x,
-                restart_draw_y,
-                button_width * restart_scale,
-                button_height * restart_scale,
-                restart_color,
-            );
-            draw_rectangle_lines(
-                restart_draw_x,
-                restart_draw_y,
-                button_width * restart_scale,
-                button_height * restart_scale,
-                4.0 * SCALE,
-                BLACK,
-            );
-            let restart_text = "Restart Game";
-            let restart_text_size = 28.0 * SCALE * GAME_OVER_BUTTON_SCALE;
-            let restart_text_width = measure_text(restart_text, None, restart_text_size as u16, 1.0).width;
-            draw_text(
-                restart_text,
-                restart_draw_x + (button_width * restart_scale - restart_text_width) / 2.0,
-                restart_draw_y + 35.0 * SCALE * GAME_OVER_BUTTON_SCALE,
-                restart_text_size,
-                WHITE,
-            );
+    #[test]
+    fn preview_applies_double_win_rule() {
+        let mut state = certain_double_five(DoubleWinRule::ObserverLoses);
+        observe_board(&mut state);
+        assert!(state.game_over);
+        assert_eq!(state.observation_winner, Some(Outcome::Win(Player::White)));
+        // 重新坍缩仍是双方成五时，达到次数上限后判平局
+        let mut state = certain_double_five(DoubleWinRule::Reroll);
+        observe_board(&mut state);
+        assert_eq!(state.observation_winner, Some(Outcome::BothWin));
+    }
 
-            let is_exit_hover = mouse_x >= exit_x && mouse_x <= exit_x + button_width &&
-                mouse_y >= button_y && mouse_y <= button_y + button_height;
-            let exit_color = if is_exit_hover { Color::new(0.7, 0.1, 0.1, 0.9) } else { Color::new(0.8, 0.0, 0.0, 0.9) };
-            let exit_scale = if is_exit_hover { HOVER_SCALE } else { 1.0 };
-            let exit_draw_x = exit_x - (button_width * (exit_scale - 1.0)) / 2.0;
-            let exit_draw_y = button_y - (button_height * (exit_scale - 1.0)) / 2.0;
-
-            draw_rectangle(
-                exit_draw_x,
-                exit_draw_y,
-                button_width * exit_scale,
-                button_height * exit_scale,
-                exit_color,
-            );
-            draw_rectangle_lines(
-                exit_draw_x,
-                exit_draw_y,
-                button_width * exit_scale,
-                button_height * exit_scale,
-                4.0 * SCALE,
-                BLACK,
-            );
-            let exit_text = "Exit Game";
-            let exit_text_size = 28.0 * SCALE * GAME_OVER_BUTTON_SCALE;
-            let exit_text_width = measure_text(exit_text, None, exit_text_size as u16, 1.0).width;
-            draw_text(
-                exit_text,
-                exit_draw_x + (button_width * exit_scale - exit_text_width) / 2.0,
-                exit_draw_y + 35.0 * SCALE * GAME_OVER_BUTTON_SCALE,
-                exit_text_size,
-                WHITE,
-            );
+    #[test]
+    fn fischer_adds_time_after_each_turn() {
+        let config = GameConfig { time_minutes: 1, time_bonus: TimeBonus::Fischer(5), ..GameConfig::default() };
+        let mut state = GameState::new(config);
+        tick_clock(&mut state, 10.0);
+        play_turns(&mut state, &[(7, 7)]);
+        assert_eq!(state.clocks, [55.0, 60.0]);
+        tick_clock(&mut state, 61.0);
+        assert!(state.game_over);
+        assert_eq!(state.observation_winner, Some(Outcome::Timeout(Player::White)));
+    }
+
+    #[test]
+    fn byo_yomi_period_resets_each_turn() {
+        let config = GameConfig { time_minutes: 1, time_bonus: TimeBonus::ByoYomi(3, 10), ..GameConfig::default() };
+        let mut state = GameState::new(config);
+        state.clocks[0] = 0.0;
+        tick_clock(&mut state, 6.0);
+        assert_eq!(state.periods[0], 3);
+        play_turns(&mut state, &[(7, 7)]);
+        assert_eq!(state.period_clock, 10.0);
+        // 白方主用时未用完，读秒不动
+        tick_clock(&mut state, 6.0);
+        assert_eq!((state.clocks[1], state.period_clock), (54.0, 10.0));
+        play_turns(&mut state, &[(7, 8)]);
+        // 一帧跨过两次读秒只用掉两次
+        tick_clock(&mut state, 25.0);
+        assert_eq!(state.periods[0], 1);
+        assert!((state.period_clock - 5.0).abs() < 1e-4);
+        assert!(!state.game_over);
+    }
+
+    #[test]
+    fn entangled_pairs_collapse_together() {
+        for (entangle, same) in [(EntangleRule::Opposite, false), (EntangleRule::Same, true)] {
+            let mut state = GameState::new(GameConfig { entangle, ..GameConfig::default() });
+            assert!(place_pair(&mut state, (7, 7), (3, 3)));
+            assert!(state.turn_complete());
+            let expected = if same { ProbPiece::BLACK_90 } else { ProbPiece::BLACK_10 };
+            assert_eq!(state.board[3][3], expected);
+            for _ in 0..50 {
+                let collapsed = collapse_board(&state.board, &state.correlations);
+                assert_ne!(collapsed[7][7], DefinitePiece::Empty);
+                assert_eq!(collapsed[7][7] == collapsed[3][3], same);
+            }
         }
+    }
 
-        next_frame().await;
+    #[test]
+    fn entangled_partner_follows_drift() {
+        let config = GameConfig { entangle: EntangleRule::Opposite, drift: Drift::Even(5), ..GameConfig::default() };
+        let mut state = GameState::new(config);
+        assert!(place_pair(&mut state, (7, 7), (3, 3)));
+        assert!(!place_pair(&mut state, (7, 8), (7, 9)));
+        end_turn(&mut state);
+        assert!(!place_pair(&mut state, (7, 7), (0, 0)));
+        assert!(place_piece(&mut state, 7, 8));
+        end_turn(&mut state);
+        assert_eq!(state.board[7][7], ProbPiece::Stone(85));
+        assert_eq!(state.board[3][3], ProbPiece::Stone(15));
+    }
+
+    #[test]
+    fn superposed_stone_appears_on_one_point() {
+        let mut state = GameState::new(GameConfig { superposition: true, ..GameConfig::default() });
+        assert!(place_superposed(&mut state, (7, 7), (3, 3)));
+        assert_eq!(state.board[3][3], ProbPiece::Superposed(90));
+        let mut seen = [false; 2];
+        for _ in 0..100 {
+            let collapsed = collapse_board(&state.board, &state.correlations);
+            let occupied = [collapsed[7][7], collapsed[3][3]].map(|piece| piece != DefinitePiece::Empty);
+            assert!(occupied[0] != occupied[1]);
+            seen[occupied[1] as usize] = true;
+        }
+        assert_eq!(seen, [true, true]);
+    }
+
+    #[test]
+    fn superposed_points_drift_together() {
+        let config = GameConfig { superposition: true, drift: Drift::Owner(5), ..GameConfig::default() };
+        let mut state = GameState::new(config);
+        assert!(place_superposed(&mut state, (7, 7), (3, 3)));
+        end_turn(&mut state);
+        assert!(place_piece(&mut state, 7, 8));
+        end_turn(&mut state);
+        assert_eq!(state.board[7][7], ProbPiece::Superposed(95));
+        assert_eq!(state.board[3][3], ProbPiece::Superposed(95));
+    }
+
+    // 依次用纠缠、叠加落子和三种能力卡走完五回合
+    fn game_with_cards() -> GameState {
+        let config = GameConfig {
+            tiers: ProbTiers::StrongOnly,
+            entangle: EntangleRule::Opposite,
+            superposition: true,
+            power_cards: 1,
+            time_minutes: 1,
+            time_bonus: TimeBonus::ByoYomi(2, 10),
+            ..GameConfig::default()
+        };
+        let mut state = GameState::new(config);
+        assert!(place_pair(&mut state, (7, 7), (3, 3)));
+        end_turn(&mut state);
+        assert!(place_superposed(&mut state, (5, 5), (6, 6)));
+        end_turn(&mut state);
+        // 移除卡只能对对方的棋子使用，叠加棋子两个点一起移除
+        assert!(!can_play_card(&state, PowerCard::Remove, (7, 7)));
+        assert!(play_card(&mut state, PowerCard::Remove, (6, 6)));
+        assert_eq!((state.board[5][5], state.board[6][6]), (ProbPiece::Empty, ProbPiece::Empty));
+        assert!(state.correlations.superpositions.is_empty());
+        assert_eq!(state.retired.superpositions.len(), 1);
+        // 每回合至多使用一张
+        assert!(!can_play_card(&state, PowerCard::Flip, (7, 7)));
+        assert!(place_piece(&mut state, 0, 0));
+        end_turn(&mut state);
+        // 翻转卡连同纠缠的另一枚一起翻转
+        assert!(!can_play_card(&state, PowerCard::Flip, (1, 1)));
+        assert!(play_card(&mut state, PowerCard::Flip, (7, 7)));
+        assert_eq!((state.board[7][7], state.board[3][3]), (ProbPiece::BLACK_10, ProbPiece::BLACK_90));
+        assert!(place_piece(&mut state, 0, 1));
+        end_turn(&mut state);
+        // 坍缩卡确定目标的颜色，纠缠的另一枚随之确定
+        let play = CardRecord {
+            turn: state.turn,
+            move_index: state.moves.len(),
+            card: PowerCard::Collapse,
+            target: (3, 3),
+            colour: Some(Player::White),
+        };
+        assert!(can_play_card(&state, play.card, play.target));
+        apply_card(&mut state, play);
+        assert_eq!((state.board[3][3], state.board[7][7]), (ProbPiece::Stone(0), ProbPiece::Stone(100)));
+        assert_eq!(state.cards[Player::Black as usize], [0, 1, 0]);
+        assert!(place_piece(&mut state, 0, 2));
+        end_turn(&mut state);
+        assert!(!can_play_card(&state, PowerCard::Flip, (7, 7)));
+        state
+    }
+
+    #[test]
+    fn power_cards_act_on_linked_stones() {
+        let state = game_with_cards();
+        assert_eq!(state.cards_played.len(), 3);
+        assert!(state.correlations.entanglements.is_empty());
+        assert_eq!(state.observed, vec![(7, 7), (3, 3)]);
+    }
+
+    #[test]
+    fn record_round_trip() {
+        let mut state = game_with_cards();
+        state.clocks = [0.0, 42.5];
+        state.periods = [1, 2];
+        state.period_clock = 4.5;
+        let record = state.to_record();
+        let parsed = GameRecord::from_text(&record.to_text()).unwrap();
+        assert_eq!(parsed, record);
+        let restored = GameState::from_record(&parsed);
+        assert_eq!(restored.board, state.board);
+        assert_eq!(restored.turn, state.turn);
+        assert_eq!(restored.cards_played, state.cards_played);
+        assert_eq!(restored.observed, state.observed);
+        assert_eq!(restored.all_correlations(), state.all_correlations());
+        assert_eq!((restored.clocks, restored.periods, restored.period_clock), (state.clocks, state.periods, 4.5));
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,345 @@
+use macroquad::prelude::*;
+
+use crate::i18n::{fill, Strings, LANGUAGES};
+use crate::layout::Layout;
+use crate::settings::{AnimationSpeed, Settings};
+use crate::text::{draw_ui_text, measure_ui_text};
+use crate::theme::{available_themes, Theme};
+use crate::ui::{Button, ButtonStyle, UiAction};
+use crate::{
+    DoubleWinRule, Drift, EntangleRule, GameConfig, OpeningRule, Opponent, PreviewRule, ProbTiers, StonesPerTurn, TimeBonus,
+    WinRule,
+};
+
+// 菜单按钮列的上下边界（基准坐标）
+const MENU_TOP: f32 = 200.0;
+const MENU_BOTTOM: f32 = 870.0;
+
+// 新游戏界面的可选项
+const OPPONENTS: [Opponent; 3] = [Opponent::Human, Opponent::ComputerWhite, Opponent::ComputerBlack];
+const BOARD_SIZES: [usize; 4] = [9, 13, 15, 19];
+const PROB_TIERS: [ProbTiers; 3] = [ProbTiers::Rotate, ProbTiers::StrongOnly, ProbTiers::WeakOnly];
+const PREVIEW_COUNTS: [u8; 3] = [1, 2, 3];
+const PREVIEW_RULES: [PreviewRule; 4] = [
+    PreviewRule::PerTurn,
+    PreviewRule::PerGame,
+    PreviewRule::Bank,
+    PreviewRule::CostsPlacement,
+];
+const DOUBLE_WIN_RULES: [DoubleWinRule; 5] = [
+    DoubleWinRule::Draw,
+    DoubleWinRule::ObserverLoses,
+    DoubleWinRule::ObserverWins,
+    DoubleWinRule::CountLines,
+    DoubleWinRule::Reroll,
+];
+const ENTANGLE_RULES: [EntangleRule; 3] = [EntangleRule::Off, EntangleRule::Opposite, EntangleRule::Same];
+const POWER_CARD_COUNTS: [u8; 4] = [0, 1, 2, 3];
+const DRIFTS: [Drift; 5] = [Drift::Off, Drift::Even(2), Drift::Even(5), Drift::Owner(2), Drift::Owner(5)];
+// 用时预设：主用时（分钟）与加时方式
+const TIME_CONTROLS: [(u32, TimeBonus); 9] = [
+    (0, TimeBonus::None),
+    (5, TimeBonus::None),
+    (10, TimeBonus::None),
+    (20, TimeBonus::None),
+    (3, TimeBonus::Fischer(2)),
+    (5, TimeBonus::Fischer(5)),
+    (10, TimeBonus::Fischer(10)),
+    (0, TimeBonus::ByoYomi(3, 30)),
+    (10, TimeBonus::ByoYomi(5, 30)),
+];
+const WIN_RULES: [WinRule; 4] = [
+    WinRule::Freestyle,
+    WinRule::Exact,
+    WinRule::BlackExact,
+    WinRule::WhiteExact,
+];
+const VOLUMES: [u8; 11] = [0, 10, 20, 30, 40, 50, 60, 70, 80, 90, 100];
+const WIN_LENGTHS: [usize; 3] = [5, 6, 7];
+const STONES_PER_TURN: [StonesPerTurn; 4] = [
+    StonesPerTurn::Fixed(1),
+    StonesPerTurn::Fixed(2),
+    StonesPerTurn::Fixed(3),
+    StonesPerTurn::Connect6,
+];
+const OPENING_RULES: [OpeningRule; 4] = [
+    OpeningRule::Standard,
+    OpeningRule::Swap,
+    OpeningRule::Swap2,
+    OpeningRule::ProbSwap,
+];
+const ANIMATION_SPEEDS: [AnimationSpeed; 4] = [
+    AnimationSpeed::Off,
+    AnimationSpeed::Slow,
+    AnimationSpeed::Normal,
+    AnimationSpeed::Fast,
+];
+
+// 菜单按钮外观
+fn menu_style(scale: f32) -> ButtonStyle {
+    ButtonStyle {
+        color: Color::new(0.2, 0.4, 0.8, 0.9),
+        hover_color: Color::new(0.1, 0.3, 0.7, 0.9),
+        disabled_color: Color::new(0.5, 0.5, 0.5, 0.7),
+        text_color: WHITE,
+        text_size: 26.0 * scale,
+        border_width: 0.0,
+    }
+}
+
+// 纵向排列的一列菜单按钮（标题下方居中）
+fn button_column(layout: &Layout, items: Vec<(UiAction, String, bool)>) -> Vec<Button> {
+    let scale = layout.scale;
+    // 选项较多时压缩间距与按钮高度，保证整列不超出窗口底部
+    let gaps = items.len().saturating_sub(1).max(1) as f32;
+    let spacing = ((MENU_BOTTOM - MENU_TOP - 56.0) / gaps).min(64.0);
+    let height = (spacing - 8.0).min(56.0);
+    let style = ButtonStyle {
+        text_size: (height * 0.7).min(26.0) * scale,
+        ..menu_style(scale)
+    };
+    let height = height * scale;
+    let spacing = spacing * scale;
+    let width = 420.0 * scale;
+    let x = layout.center_x() - width / 2.0;
+
+    items
+        .into_iter()
+        .enumerate()
+        .map(|(index, (action, label, enabled))| {
+            let y = layout.ui_y(MENU_TOP) + index as f32 * spacing;
+            Button::new(action, Rect::new(x, y, width, height), label, style).enabled(enabled)
+        })
+        .collect()
+}
+
+// 主菜单按钮：没有存档或复盘记录时对应按钮不可用
+pub fn main_menu_buttons(
+    layout: &Layout,
+    strings: &Strings,
+    has_saved_game: bool,
+    has_finished_game: bool,
+) -> Vec<Button> {
+    button_column(
+        layout,
+        vec![
+            (UiAction::NewGame, strings.new_game.to_owned(), true),
+            (UiAction::LoadGame, strings.load_game.to_owned(), has_saved_game),
+            (UiAction::Replay, strings.replay_last_game.to_owned(), has_finished_game),
+            (UiAction::Settings, strings.settings.to_owned(), true),
+            (UiAction::Exit, strings.quit.to_owned(), true),
+        ],
+    )
+}
+
+// 新游戏设置按钮：点击选项按钮切换取值
+pub fn new_game_buttons(layout: &Layout, strings: &Strings, config: &GameConfig) -> Vec<Button> {
+    let opponent = match config.opponent {
+        Opponent::Human => strings.opponent_human,
+        Opponent::ComputerWhite => strings.opponent_computer_white,
+        Opponent::ComputerBlack => strings.opponent_computer_black,
+    };
+    let tiers = match config.tiers {
+        ProbTiers::Rotate => strings.tiers_rotate,
+        ProbTiers::StrongOnly => strings.tiers_strong,
+        ProbTiers::WeakOnly => strings.tiers_weak,
+    };
+    let win_rule = match config.win_rule {
+        WinRule::Freestyle => strings.win_rule_freestyle,
+        WinRule::Exact => strings.win_rule_exact,
+        WinRule::BlackExact => strings.win_rule_black_exact,
+        WinRule::WhiteExact => strings.win_rule_white_exact,
+    };
+    let stones_per_turn = match config.stones_per_turn {
+        StonesPerTurn::Fixed(count) => count.to_string(),
+        StonesPerTurn::Connect6 => strings.stones_per_turn_connect6.to_owned(),
+    };
+    let opening = match config.opening {
+        OpeningRule::Standard => strings.opening_standard,
+        OpeningRule::Swap => strings.opening_swap,
+        OpeningRule::Swap2 => strings.opening_swap2,
+        OpeningRule::ProbSwap => strings.opening_prob_swap,
+    };
+    let preview_rule = match config.preview_rule {
+        PreviewRule::PerTurn => strings.preview_rule_per_turn,
+        PreviewRule::PerGame => strings.preview_rule_per_game,
+        PreviewRule::Bank => strings.preview_rule_bank,
+        PreviewRule::CostsPlacement => strings.preview_rule_costs,
+    };
+    let double_win = match config.double_win {
+        DoubleWinRule::Draw => strings.double_win_draw,
+        DoubleWinRule::ObserverLoses => strings.double_win_observer_loses,
+        DoubleWinRule::ObserverWins => strings.double_win_observer_wins,
+        DoubleWinRule::CountLines => strings.double_win_count_lines,
+        DoubleWinRule::Reroll => strings.double_win_reroll,
+    };
+    let entangle = match config.entangle {
+        EntangleRule::Off => strings.off,
+        EntangleRule::Opposite => strings.entangle_opposite,
+        EntangleRule::Same => strings.entangle_same,
+    };
+    let drift = match config.drift {
+        Drift::Off => strings.off.to_owned(),
+        Drift::Even(step) => fill(strings.drift_even, &[&step]),
+        Drift::Owner(step) => fill(strings.drift_owner, &[&step]),
+    };
+    let power_cards = match config.power_cards {
+        0 => strings.off.to_owned(),
+        count => fill(strings.power_cards_each, &[&count]),
+    };
+    let time_control = match (config.time_minutes, config.time_bonus) {
+        (0, TimeBonus::None | TimeBonus::Fischer(_)) => strings.off.to_owned(),
+        (minutes, TimeBonus::None) => fill(strings.time_minutes, &[&minutes]),
+        (minutes, TimeBonus::Fischer(seconds)) => fill(strings.time_fischer, &[&minutes, &seconds]),
+        (0, TimeBonus::ByoYomi(periods, seconds)) => fill(strings.time_byo_yomi, &[&periods, &seconds]),
+        (minutes, TimeBonus::ByoYomi(periods, seconds)) => {
+            fill(strings.time_main_byo_yomi, &[&minutes, &periods, &seconds])
+        }
+    };
+
+    button_column(
+        layout,
+        vec![
+            (UiAction::CycleOpponent, fill(strings.opponent, &[&opponent]), true),
+            (UiAction::CycleBoardSize, fill(strings.board, &[&config.board_size, &config.board_size]), true),
+            (UiAction::CycleTiers, fill(strings.pieces, &[&tiers]), true),
+            (UiAction::CyclePreviews, fill(strings.previews, &[&config.previews]), true),
+            (UiAction::CyclePreviewRule, fill(strings.preview_rule, &[&preview_rule]), true),
+            (UiAction::CycleEntangle, fill(strings.entangle_rule, &[&entangle]), true),
+            (UiAction::ToggleSuperposition, fill(strings.superposition, &[&on_off(strings, config.superposition)]), true),
+            (UiAction::CycleDrift, fill(strings.drift, &[&drift]), true),
+            (UiAction::CyclePowerCards, fill(strings.power_cards, &[&power_cards]), true),
+            (UiAction::CycleTimeControl, fill(strings.time_control, &[&time_control]), true),
+            (UiAction::CycleWinRule, fill(strings.win_rule, &[&win_rule]), true),
+            (UiAction::CycleWinLength, fill(strings.win_length, &[&config.win_length]), true),
+            (UiAction::CycleDoubleWin, fill(strings.double_win, &[&double_win]), true),
+            (UiAction::CycleStonesPerTurn, fill(strings.stones_per_turn, &[&stones_per_turn]), true),
+            // 禁手棋形按五子定义，其他获胜子数下不可用
+            (
+                UiAction::ToggleRenju,
+                fill(strings.renju, &[&on_off(strings, config.renju)]),
+                config.win_length == 5,
+            ),
+            (UiAction::CycleOpening, fill(strings.opening, &[&opening]), true),
+            (UiAction::StartGame, strings.start_game.to_owned(), true),
+            (UiAction::Back, strings.back.to_owned(), true),
+        ],
+    )
+}
+
+fn on_off(strings: &Strings, value: bool) -> &'static str {
+    if value { strings.on } else { strings.off }
+}
+
+// 设置界面按钮：点击切换取值，修改后立即保存
+pub fn settings_buttons(layout: &Layout, strings: &Strings, settings: &Settings) -> Vec<Button> {
+    let animation_speed = match settings.animation_speed {
+        AnimationSpeed::Off => strings.off,
+        AnimationSpeed::Slow => strings.speed_slow,
+        AnimationSpeed::Normal => strings.speed_normal,
+        AnimationSpeed::Fast => strings.speed_fast,
+    };
+    // 语言名称始终以该语言自身显示
+    let language = crate::i18n::strings(settings.language).language_name;
+
+    button_column(
+        layout,
+        vec![
+            (UiAction::CycleTheme, fill(strings.theme, &[&settings.theme]), true),
+            (UiAction::CycleLanguage, fill(strings.language, &[&language]), true),
+            (UiAction::ToggleSound, fill(strings.sound, &[&on_off(strings, settings.sound)]), true),
+            (UiAction::CycleVolume, fill(strings.volume, &[&settings.volume]), settings.sound),
+            (
+                UiAction::ToggleProbText,
+                fill(strings.probability_text, &[&on_off(strings, settings.show_prob_text)]),
+                true,
+            ),
+            (
+                UiAction::ToggleSplitStones,
+                fill(strings.split_stones, &[&on_off(strings, settings.split_stones)]),
+                true,
+            ),
+            (
+                UiAction::ToggleCoordinates,
+                fill(strings.coordinates, &[&on_off(strings, settings.show_coordinates)]),
+                true,
+            ),
+            (
+                UiAction::ToggleConfirmEndTurn,
+                fill(strings.confirm_end_turn_setting, &[&on_off(strings, settings.confirm_end_turn)]),
+                true,
+            ),
+            (UiAction::CycleAnimationSpeed, fill(strings.animation_speed, &[&animation_speed]), true),
+            (UiAction::Back, strings.back.to_owned(), true),
+        ],
+    )
+}
+
+// 修改某一项设置：返回该动作是否为设置项
+pub fn change_setting(settings: &mut Settings, action: UiAction, step: i32) -> bool {
+    match action {
+        UiAction::CycleTheme => {
+            let names: Vec<String> = available_themes().into_iter().map(|theme| theme.name).collect();
+            settings.theme = cycle(&names, &settings.theme, step).clone();
+        }
+        UiAction::CycleLanguage => settings.language = *cycle(&LANGUAGES, &settings.language, step),
+        UiAction::ToggleSound => settings.sound = !settings.sound,
+        UiAction::CycleVolume => settings.volume = *cycle(&VOLUMES, &settings.volume, step),
+        UiAction::ToggleProbText => settings.show_prob_text = !settings.show_prob_text,
+        UiAction::ToggleSplitStones => settings.split_stones = !settings.split_stones,
+        UiAction::ToggleCoordinates => settings.show_coordinates = !settings.show_coordinates,
+        UiAction::ToggleConfirmEndTurn => settings.confirm_end_turn = !settings.confirm_end_turn,
+        UiAction::CycleAnimationSpeed => {
+            settings.animation_speed = *cycle(&ANIMATION_SPEEDS, &settings.animation_speed, step)
+        }
+        _ => return false,
+    }
+    true
+}
+
+// 在选项列表中前后切换
+fn cycle<'a, T: PartialEq>(options: &'a [T], current: &T, step: i32) -> &'a T {
+    let index = options.iter().position(|option| option == current).unwrap_or(0) as i32;
+    let len = options.len() as i32;
+    &options[(index + step).rem_euclid(len) as usize]
+}
+
+// 切换新游戏设置中的某一项：返回该动作是否为设置项
+pub fn cycle_option(config: &mut GameConfig, action: UiAction, step: i32) -> bool {
+    match action {
+        UiAction::CycleOpponent => config.opponent = *cycle(&OPPONENTS, &config.opponent, step),
+        UiAction::CycleBoardSize => config.board_size = *cycle(&BOARD_SIZES, &config.board_size, step),
+        UiAction::CycleTiers => config.tiers = *cycle(&PROB_TIERS, &config.tiers, step),
+        UiAction::CyclePreviews => config.previews = *cycle(&PREVIEW_COUNTS, &config.previews, step),
+        UiAction::CyclePreviewRule => config.preview_rule = *cycle(&PREVIEW_RULES, &config.preview_rule, step),
+        UiAction::CycleTimeControl => {
+            (config.time_minutes, config.time_bonus) =
+                *cycle(&TIME_CONTROLS, &(config.time_minutes, config.time_bonus), step)
+        }
+        UiAction::CycleWinRule => config.win_rule = *cycle(&WIN_RULES, &config.win_rule, step),
+        UiAction::CycleWinLength => config.win_length = *cycle(&WIN_LENGTHS, &config.win_length, step),
+        UiAction::CycleStonesPerTurn => config.stones_per_turn = *cycle(&STONES_PER_TURN, &config.stones_per_turn, step),
+        UiAction::ToggleRenju => config.renju = !config.renju,
+        UiAction::CycleEntangle => config.entangle = *cycle(&ENTANGLE_RULES, &config.entangle, step),
+        UiAction::ToggleSuperposition => config.superposition = !config.superposition,
+        UiAction::CycleDrift => config.drift = *cycle(&DRIFTS, &config.drift, step),
+        UiAction::CyclePowerCards => config.power_cards = *cycle(&POWER_CARD_COUNTS, &config.power_cards, step),
+        UiAction::CycleDoubleWin => config.double_win = *cycle(&DOUBLE_WIN_RULES, &config.double_win, step),
+        UiAction::CycleOpening => config.opening = *cycle(&OPENING_RULES, &config.opening, step),
+        _ => return false,
+    }
+    true
+}
+
+// 绘制菜单标题
+pub fn draw_menu_title(layout: &Layout, theme: &Theme, title: &str) {
+    let title_size = 56.0 * layout.scale;
+    let title_width = measure_ui_text(title, title_size).width;
+    draw_ui_text(
+        title,
+        layout.center_x() - title_width / 2.0,
+        layout.ui_y(130.0),
+        title_size,
+        theme.text,
+    );
+}
@@ -0,0 +1,545 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::{
+    DoubleWinRule, Drift, EntangleRule, GameConfig, OpeningChoice, OpeningRule, Opponent, Outcome, Player, PowerCard,
+    PreviewRule, ProbPiece, ProbTiers, StonesPerTurn, TimeBonus, WinRule,
+};
+
+// 存档文件：未完成的对局与最近一局已结束的对局（用于复盘）
+const SAVED_GAME_FILE: &str = "saved_game.txt";
+const FINISHED_GAME_FILE: &str = "last_game.txt";
+const RECORD_HEADER: &str = "# probability gomoku record v1";
+
+// 单步落子记录
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MoveRecord {
+    pub player: Player,
+    pub row: usize,
+    pub col: usize,
+    pub piece: ProbPiece,
+}
+
+// 一次能力卡使用：所在回合、使用时已有的落子数、目标点，坍缩卡另记坍缩出的颜色
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CardRecord {
+    pub turn: usize,
+    pub move_index: usize,
+    pub card: PowerCard,
+    pub target: (usize, usize), // 坍缩叠加棋子时为棋子最终出现的点
+    pub colour: Option<Player>,
+}
+
+// 对局记录：设置、落子顺序、剩余用时和结果
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameRecord {
+    pub config: GameConfig,
+    pub moves: Vec<MoveRecord>,
+    pub opening_choices: Vec<OpeningChoice>,
+    pub previews: Vec<usize>, // 每次预览所在的回合
+    pub entanglements: Vec<(usize, bool)>, // 纠缠棋子对：第一枚的落子下标与是否同色
+    pub superpositions: Vec<usize>,         // 叠加棋子：第一个点的落子下标
+    pub cards: Vec<CardRecord>,
+    pub clocks: Option<[f32; 2]>,
+    pub periods: Option<[u8; 2]>, // 读秒对局中双方剩余读秒次数
+    pub period_clock: Option<f32>, // 读秒对局中当前回合读秒剩余时间
+    pub result: Option<Outcome>,
+}
+
+impl GameRecord {
+    // 序列化为逐行的 "键 值" 文本
+    pub fn to_text(&self) -> String {
+        let opponent = match self.config.opponent {
+            Opponent::Human => "human",
+            Opponent::ComputerWhite => "computer-white",
+            Opponent::ComputerBlack => "computer-black",
+        };
+        let tiers = match self.config.tiers {
+            ProbTiers::Rotate => "rotate",
+            ProbTiers::StrongOnly => "strong",
+            ProbTiers::WeakOnly => "weak",
+        };
+        let win_rule = match self.config.win_rule {
+            WinRule::Freestyle => "freestyle",
+            WinRule::Exact => "exact",
+            WinRule::BlackExact => "black-exact",
+            WinRule::WhiteExact => "white-exact",
+        };
+        let stones = match self.config.stones_per_turn {
+            StonesPerTurn::Fixed(count) => count.to_string(),
+            StonesPerTurn::Connect6 => "connect6".to_owned(),
+        };
+        let preview_rule = match self.config.preview_rule {
+            PreviewRule::PerTurn => "per-turn",
+            PreviewRule::PerGame => "per-game",
+            PreviewRule::Bank => "bank",
+            PreviewRule::CostsPlacement => "costs-placement",
+        };
+        let double_win = match self.config.double_win {
+            DoubleWinRule::Draw => "draw",
+            DoubleWinRule::ObserverLoses => "observer-loses",
+            DoubleWinRule::ObserverWins => "observer-wins",
+            DoubleWinRule::CountLines => "count-lines",
+            DoubleWinRule::Reroll => "reroll",
+        };
+        let entangle = match self.config.entangle {
+            EntangleRule::Off => "off",
+            EntangleRule::Opposite => "opposite",
+            EntangleRule::Same => "same",
+        };
+        let opening = match self.config.opening {
+            OpeningRule::Standard => "standard",
+            OpeningRule::Swap => "swap",
+            OpeningRule::Swap2 => "swap2",
+            OpeningRule::ProbSwap => "prob-swap",
+        };
+
+        let mut lines = vec![
+            RECORD_HEADER.to_owned(),
+            format!("board_size {}", self.config.board_size),
+            format!("opponent {}", opponent),
+            format!("tiers {}", tiers),
+            format!("previews {}", self.config.previews),
+            format!("preview_rule {}", preview_rule),
+            format!("time {}", self.config.time_minutes),
+            match self.config.time_bonus {
+                TimeBonus::None => "time_bonus none".to_owned(),
+                TimeBonus::Fischer(seconds) => format!("time_bonus fischer {}", seconds),
+                TimeBonus::ByoYomi(periods, seconds) => format!("time_bonus byo-yomi {} {}", periods, seconds),
+            },
+            format!("win_rule {}", win_rule),
+            format!("win_length {}", self.config.win_length),
+            format!("double_win {}", double_win),
+            format!("entangle_rule {}", entangle),
+            format!("superposition {}", if self.config.superposition { "on" } else { "off" }),
+            format!("power_cards {}", self.config.power_cards),
+            match self.config.drift {
+                Drift::Off => "drift off".to_owned(),
+                Drift::Even(step) => format!("drift even {}", step),
+                Drift::Owner(step) => format!("drift owner {}", step),
+            },
+            format!("stones {}", stones),
+            format!("opening {}", opening),
+            format!("renju {}", if self.config.renju { "on" } else { "off" }),
+        ];
+        if let Some([black, white]) = self.clocks {
+            lines.push(format!("clocks {:.1} {:.1}", black, white));
+        }
+        if let Some([black, white]) = self.periods {
+            lines.push(format!("periods {} {}", black, white));
+        }
+        if let Some(seconds) = self.period_clock {
+            lines.push(format!("period_clock {:.1}", seconds));
+        }
+        for mv in &self.moves {
+            let player = match mv.player {
+                Player::Black => "B",
+                Player::White => "W",
+            };
+            lines.push(format!("move {} {} {} {}", player, mv.row, mv.col, mv.piece.black_percent()));
+        }
+        for choice in &self.opening_choices {
+            lines.push(format!("choice {}", choice.key()));
+        }
+        for turn in &self.previews {
+            lines.push(format!("preview {}", turn));
+        }
+        for (index, same) in &self.entanglements {
+            lines.push(format!("entangle {} {}", index, if *same { "same" } else { "opposite" }));
+        }
+        for index in &self.superpositions {
+            lines.push(format!("superpose {}", index));
+        }
+        for play in &self.cards {
+            let (row, col) = play.target;
+            let mut line = format!("card {} {} {} {} {}", play.turn, play.move_index, play.card.key(), row, col);
+            match play.colour {
+                Some(Player::Black) => line.push_str(" B"),
+                Some(Player::White) => line.push_str(" W"),
+                None => {}
+            }
+            lines.push(line);
+        }
+        if let Some(result) = self.result {
+            lines.push(format!("result {}", result.key()));
+        }
+        lines.join("\n") + "\n"
+    }
+
+    // 从文本解析对局记录：格式错误时返回None
+    pub fn from_text(text: &str) -> Option<GameRecord> {
+        let mut lines = text.lines();
+        if lines.next()? != RECORD_HEADER {
+            return None;
+        }
+
+        let mut record = GameRecord {
+            config: GameConfig::default(),
+            moves: Vec::new(),
+            opening_choices: Vec::new(),
+            previews: Vec::new(),
+            entanglements: Vec::new(),
+            superpositions: Vec::new(),
+            cards: Vec::new(),
+            clocks: None,
+            periods: None,
+            period_clock: None,
+            result: None,
+        };
+        for line in lines {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            match key {
+                "board_size" => record.config.board_size = value.parse().ok().filter(|size| (5..=25).contains(size))?,
+                "opponent" => {
+                    record.config.opponent = match value {
+                        "human" => Opponent::Human,
+                        "computer-white" => Opponent::ComputerWhite,
+                        "computer-black" => Opponent::ComputerBlack,
+                        _ => return None,
+                    }
+                }
+                "tiers" => {
+                    record.config.tiers = match value {
+                        "rotate" => ProbTiers::Rotate,
+                        "strong" => ProbTiers::StrongOnly,
+                        "weak" => ProbTiers::WeakOnly,
+                        _ => return None,
+                    }
+                }
+                "win_rule" => {
+                    record.config.win_rule = match value {
+                        "freestyle" => WinRule::Freestyle,
+                        "exact" => WinRule::Exact,
+                        "black-exact" => WinRule::BlackExact,
+                        "white-exact" => WinRule::WhiteExact,
+                        _ => return None,
+                    }
+                }
+                "win_length" => record.config.win_length = value.parse().ok().filter(|length| (3..=7).contains(length))?,
+                "double_win" => {
+                    record.config.double_win = match value {
+                        "draw" => DoubleWinRule::Draw,
+                        "observer-loses" => DoubleWinRule::ObserverLoses,
+                        "observer-wins" => DoubleWinRule::ObserverWins,
+                        "count-lines" => DoubleWinRule::CountLines,
+                        "reroll" => DoubleWinRule::Reroll,
+                        _ => return None,
+                    }
+                }
+                "stones" => {
+                    record.config.stones_per_turn = match value {
+                        "connect6" => StonesPerTurn::Connect6,
+                        count => StonesPerTurn::Fixed(count.parse().ok().filter(|&count| count > 0)?),
+                    }
+                }
+                "opening" => {
+                    record.config.opening = match value {
+                        "standard" => OpeningRule::Standard,
+                        "swap" => OpeningRule::Swap,
+                        "swap2" => OpeningRule::Swap2,
+                        "prob-swap" => OpeningRule::ProbSwap,
+                        _ => return None,
+                    }
+                }
+                "choice" => record.opening_choices.push(OpeningChoice::from_key(value)?),
+                "renju" => {
+                    record.config.renju = match value {
+                        "on" => true,
+                        "off" => false,
+                        _ => return None,
+                    }
+                }
+                "previews" => record.config.previews = value.parse().ok()?,
+                "preview_rule" => {
+                    record.config.preview_rule = match value {
+                        "per-turn" => PreviewRule::PerTurn,
+                        "per-game" => PreviewRule::PerGame,
+                        "bank" => PreviewRule::Bank,
+                        "costs-placement" => PreviewRule::CostsPlacement,
+                        _ => return None,
+                    }
+                }
+                "preview" => record.previews.push(value.parse().ok()?),
+                "superpose" => record.superpositions.push(value.parse().ok()?),
+                "power_cards" => record.config.power_cards = value.parse().ok()?,
+                "card" => {
+                    let fields: Vec<&str> = value.split(' ').collect();
+                    let (turn, move_index, card, row, col, colour) = match fields[..] {
+                        [turn, move_index, card, row, col] => (turn, move_index, card, row, col, None),
+                        [turn, move_index, card, row, col, colour] => (turn, move_index, card, row, col, Some(colour)),
+                        _ => return None,
+                    };
+                    let card = PowerCard::from_key(card)?;
+                    let colour = match colour {
+                        Some("B") => Some(Player::Black),
+                        Some("W") => Some(Player::White),
+                        None => None,
+                        _ => return None,
+                    };
+                    // 只有坍缩卡记有颜色
+                    if colour.is_some() != (card == PowerCard::Collapse) {
+                        return None;
+                    }
+                    record.cards.push(CardRecord {
+                        turn: turn.parse().ok()?,
+                        move_index: move_index.parse().ok()?,
+                        card,
+                        target: (row.parse().ok()?, col.parse().ok()?),
+                        colour,
+                    });
+                }
+                "entangle" => {
+                    let (index, same) = value.split_once(' ')?;
+                    let same = match same {
+                        "same" => true,
+                        "opposite" => false,
+                        _ => return None,
+                    };
+                    record.entanglements.push((index.parse().ok()?, same));
+                }
+                "time" => record.config.time_minutes = value.parse().ok()?,
+                "superposition" => {
+                    record.config.superposition = match value {
+                        "on" => true,
+                        "off" => false,
+                        _ => return None,
+                    }
+                }
+                "entangle_rule" => {
+                    record.config.entangle = match value {
+                        "off" => EntangleRule::Off,
+                        "opposite" => EntangleRule::Opposite,
+                        "same" => EntangleRule::Same,
+                        _ => return None,
+                    }
+                }
+                "drift" => {
+                    let fields: Vec<&str> = value.split(' ').collect();
+                    record.config.drift = match fields[..] {
+                        ["off"] => Drift::Off,
+                        ["even", step] => Drift::Even(step.parse().ok()?),
+                        ["owner", step] => Drift::Owner(step.parse().ok()?),
+                        _ => return None,
+                    }
+                }
+                "time_bonus" => {
+                    let fields: Vec<&str> = value.split(' ').collect();
+                    record.config.time_bonus = match fields[..] {
+                        ["none"] => TimeBonus::None,
+                        ["fischer", seconds] => TimeBonus::Fischer(seconds.parse().ok()?),
+                        ["byo-yomi", periods, seconds] => TimeBonus::ByoYomi(periods.parse().ok()?, seconds.parse().ok()?),
+                        _ => return None,
+                    }
+                }
+                "periods" => {
+                    let (black, white) = value.split_once(' ')?;
+                    record.periods = Some([black.parse().ok()?, white.parse().ok()?]);
+                }
+                "period_clock" => record.period_clock = Some(value.parse().ok()?),
+                "clocks" => {
+                    let (black, white) = value.split_once(' ')?;
+                    record.clocks = Some([black.parse().ok()?, white.parse().ok()?]);
+                }
+                "move" => {
+                    let fields: Vec<&str> = value.split(' ').collect();
+                    if fields.len() != 4 {
+                        return None;
+                    }
+                    let player = match fields[0] {
+                        "B" => Player::Black,
+                        "W" => Player::White,
+                        _ => return None,
+                    };
+                    let row: usize = fields[1].parse().ok()?;
+                    let col: usize = fields[2].parse().ok()?;
+                    let piece = ProbPiece::from_black_percent(fields[3].parse().ok()?)?;
+                    record.moves.push(MoveRecord { player, row, col, piece });
+                }
+                "result" => record.result = Outcome::from_key(value),
+                "" => {}
+                _ => return None,
+            }
+        }
+        if !record.is_consistent() {
+            return None;
+        }
+        // 叠加棋子在落子行中只记黑棋百分比，按叠加记录还原
+        for &start in &record.superpositions {
+            for mv in &mut record.moves[start..start + 2] {
+                mv.piece = ProbPiece::Superposed(mv.piece.black_percent());
+            }
+        }
+        Some(record)
+    }
+
+    // 读完全部行后按最终设置整体校验（各行先后顺序不限，棋盘大小可以写在落子之后）
+    fn is_consistent(&self) -> bool {
+        let size = self.config.board_size;
+        let inside = |(row, col): (usize, usize)| row < size && col < size;
+        if !self.moves.iter().all(|mv| inside((mv.row, mv.col))) || !self.cards.iter().all(|play| inside(play.target)) {
+            return false;
+        }
+
+        // 纠缠对与叠加棋子各占同一方相邻的两条落子记录，互不重叠，且本局规则允许
+        if (!self.entanglements.is_empty() && self.config.entangle == EntangleRule::Off) ||
+           (!self.superpositions.is_empty() && !self.config.superposition) {
+            return false;
+        }
+        let mut starts: Vec<usize> =
+            self.entanglements.iter().map(|&(index, _)| index).chain(self.superpositions.iter().copied()).collect();
+        starts.sort_unstable();
+        if starts.windows(2).any(|pair| pair[1] < pair[0] + 2) {
+            return false;
+        }
+        let same_player = |start: usize| self.moves.get(start + 1).is_some_and(|second| second.player == self.moves[start].player);
+        if !starts.into_iter().all(same_player) {
+            return false;
+        }
+
+        // 按顺序逐手落子：不能落在已有棋子的点上，除非该点之前被能力卡清空
+        // （移除卡清空目标点，叠加棋子被移除或坍缩时另一个点也随之清空）
+        if self.cards.windows(2).any(|pair| pair[1].move_index < pair[0].move_index) {
+            return false;
+        }
+        let mut occupant: Vec<Option<usize>> = vec![None; size * size]; // 每个点上现存棋子的落子下标
+        let mut cards = self.cards.iter().peekable();
+        for (index, mv) in self.moves.iter().enumerate() {
+            while let Some(play) = cards.next_if(|play| play.move_index <= index) {
+                let target = play.target.0 * size + play.target.1;
+                let Some(owner) = occupant[target] else {
+                    continue;
+                };
+                let partner = self
+                    .superpositions
+                    .iter()
+                    .find_map(|&start| (owner == start).then_some(start + 1).or((owner == start + 1).then_some(start)));
+                if let Some(partner) = partner.filter(|_| play.card != PowerCard::Flip) {
+                    let other = &self.moves[partner];
+                    let cell = &mut occupant[other.row * size + other.col];
+                    if *cell == Some(partner) {
+                        *cell = None;
+                    }
+                }
+                if play.card == PowerCard::Remove {
+                    occupant[target] = None;
+                }
+            }
+            let cell = &mut occupant[mv.row * size + mv.col];
+            if cell.is_some() {
+                return false;
+            }
+            *cell = Some(index);
+        }
+        true
+    }
+}
+
+// 存档目录：用户数据目录下的 probability_gomoku
+fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("probability_gomoku"))
+}
+
+fn record_path(file: &str) -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join(file))
+}
+
+fn write_record(file: &str, record: &GameRecord) -> io::Result<()> {
+    let dir = data_dir().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join(file), record.to_text())
+}
+
+fn read_record(file: &str) -> Option<GameRecord> {
+    let text = fs::read_to_string(record_path(file)?).ok()?;
+    GameRecord::from_text(&text)
+}
+
+// 保存未完成的对局
+pub fn save_game(record: &GameRecord) -> io::Result<()> {
+    write_record(SAVED_GAME_FILE, record)
+}
+
+// 保存已结束的对局，供复盘使用
+pub fn save_finished_game(record: &GameRecord) -> io::Result<()> {
+    write_record(FINISHED_GAME_FILE, record)
+}
+
+// 删除未完成对局的存档
+pub fn clear_saved_game() {
+    if let Some(path) = record_path(SAVED_GAME_FILE) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+pub fn load_saved_game() -> Option<GameRecord> {
+    read_record(SAVED_GAME_FILE)
+}
+
+pub fn load_finished_game() -> Option<GameRecord> {
+    read_record(FINISHED_GAME_FILE)
+}
+
+pub fn has_saved_game() -> bool {
+    record_path(SAVED_GAME_FILE).is_some_and(|path| path.exists())
+}
+
+pub fn has_finished_game() -> bool {
+    record_path(FINISHED_GAME_FILE).is_some_and(|path| path.exists())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(lines: &[&str]) -> Option<GameRecord> {
+        let text = std::iter::once(RECORD_HEADER).chain(lines.iter().copied()).collect::<Vec<_>>().join("\n");
+        GameRecord::from_text(&text)
+    }
+
+    #[test]
+    fn board_size_after_moves() {
+        // 棋盘大小写在落子之后：按最终的大小检查落子
+        let record = parse(&["move B 14 14 90", "move W 3 3 30", "board_size 19"]).unwrap();
+        assert_eq!(record.config.board_size, 19);
+        assert_eq!(record.moves.len(), 2);
+        assert!(parse(&["move B 14 14 90", "board_size 9"]).is_none());
+        assert!(parse(&["card 0 0 remove 12 12", "board_size 9"]).is_none());
+    }
+
+    #[test]
+    fn occupied_points() {
+        assert!(parse(&["move B 7 7 90", "move W 7 7 30"]).is_none());
+        // 被移除卡清空的点可以再次落子
+        let cleared = ["power_cards 1", "move B 7 7 90", "move W 3 3 30", "card 2 2 remove 3 3", "move B 3 3 70"];
+        assert!(parse(&cleared).is_some());
+        // 叠加棋子坍缩到一个点后，另一个点空出
+        let collapsed = [
+            "superposition on",
+            "power_cards 1",
+            "move B 7 7 90",
+            "move B 8 8 90",
+            "superpose 0",
+            "card 1 2 collapse 8 8 B",
+            "move W 7 7 30",
+        ];
+        assert!(parse(&collapsed).is_some());
+        let mut kept = collapsed;
+        kept[6] = "move W 8 8 30";
+        assert!(parse(&kept).is_none());
+    }
+
+    #[test]
+    fn correlated_moves() {
+        let pair = ["entangle_rule opposite", "move B 7 7 90", "move B 7 8 10", "entangle 0 opposite"];
+        assert!(parse(&pair).is_some());
+        // 规则未开启、缺少第二枚、两组重叠或两枚不是同一方落下
+        assert!(parse(&pair[1..]).is_none());
+        assert!(parse(&["entangle_rule opposite", "move B 7 7 90", "entangle 0 opposite"]).is_none());
+        assert!(parse(&[&pair[..], &["move B 7 9 90", "entangle 1 opposite"]].concat()).is_none());
+        assert!(parse(&["entangle_rule same", "move B 7 7 90", "move W 7 8 90", "entangle 0 same"]).is_none());
+        let both = ["entangle_rule same", "superposition on", "move B 7 7 90", "move B 7 8 90", "superpose 0", "entangle 0 same"];
+        assert!(parse(&both[..5]).is_some());
+        assert!(parse(&both).is_none());
+    }
+}
@@ -0,0 +1,175 @@
+use crate::ProbPiece;
+
+// 连珠禁手：黑方不得落在形成三三、四四或长连的交叉点
+//
+// 棋子在坍缩前没有确定颜色，因此按每枚棋子较可能的颜色（黑棋概率过半视为黑棋）
+// 看待整个棋盘，再假设新落的棋子为黑棋来判断棋形。
+// 简化处理：不递归检查组成三的活四点本身是否为禁手。
+
+// 经过一点的四个方向：横、竖、右下斜、左下斜
+const DIRECTIONS: [(i32, i32); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+// 判断棋形时沿每个方向向两侧查看的路数
+const REACH: usize = 5;
+const LINE: usize = REACH * 2 + 1;
+
+// 一条线上的交叉点：黑棋、空位，或白棋与棋盘外
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Cell {
+    Black,
+    Empty,
+    Blocked,
+}
+
+// 以 (row, col) 为中心、沿某方向的一段线，中心视为刚落下的黑棋
+fn line_through(board: &[Vec<ProbPiece>], row: usize, col: usize, (dr, dc): (i32, i32)) -> [Cell; LINE] {
+    let size = board.len() as i32;
+    let mut line = [Cell::Blocked; LINE];
+    for (index, cell) in line.iter_mut().enumerate() {
+        let step = index as i32 - REACH as i32;
+        let r = row as i32 + dr * step;
+        let c = col as i32 + dc * step;
+        if r < 0 || r >= size || c < 0 || c >= size {
+            continue;
+        }
+        *cell = match board[r as usize][c as usize] {
+            _ if step == 0 => Cell::Black,
+            ProbPiece::Empty => Cell::Empty,
+            // 叠加棋子不一定出现在该点，也不能在该点落子，按阻断处理
+            ProbPiece::Superposed(_) => Cell::Blocked,
+            piece if piece.black_percent() > 50 => Cell::Black,
+            _ => Cell::Blocked,
+        };
+    }
+    line
+}
+
+// 经过中心的黑棋连线长度
+fn run_length(line: &[Cell; LINE]) -> usize {
+    let back = (1..=REACH).take_while(|&step| line[REACH - step] == Cell::Black).count();
+    let forward = (1..=REACH).take_while(|&step| line[REACH + step] == Cell::Black).count();
+    back + forward + 1
+}
+
+// 在线上某空位补一枚黑棋
+fn with_black(line: &[Cell; LINE], index: usize) -> [Cell; LINE] {
+    let mut next = *line;
+    next[index] = Cell::Black;
+    next
+}
+
+// 再补一子即可成恰好五子的空位
+fn five_points(line: &[Cell; LINE]) -> Vec<usize> {
+    (0..LINE)
+        .filter(|&index| line[index] == Cell::Empty && run_length(&with_black(line, index)) == 5)
+        .collect()
+}
+
+// 活四：两个成五点之间恰好是连续四枚黑棋（_BBBB_）
+fn is_open_four(points: &[usize]) -> bool {
+    points.windows(2).any(|pair| pair[1] - pair[0] == 5)
+}
+
+// 线上四的个数：每个成五点算一个四，但活四两端的成五点同属一个四
+// （B_B●B_B 这样一条线上两个成五点互不相干，算作两个四）
+fn count_fours(line: &[Cell; LINE]) -> usize {
+    let points = five_points(line);
+    points.len() - points.windows(2).filter(|pair| pair[1] - pair[0] == 5).count()
+}
+
+// 再补一子即可成活四的线为三
+fn is_three(line: &[Cell; LINE]) -> bool {
+    (0..LINE).any(|index| {
+        line[index] == Cell::Empty && {
+            let next = with_black(line, index);
+            run_length(&next) < 5 && is_open_four(&five_points(&next))
+        }
+    })
+}
+
+// 黑方落在该空位是否为禁手（成五优先于禁手）
+pub fn is_forbidden(board: &[Vec<ProbPiece>], row: usize, col: usize) -> bool {
+    let lines = DIRECTIONS.map(|direction| line_through(board, row, col, direction));
+    if lines.iter().any(|line| run_length(line) == 5) {
+        return false;
+    }
+    if lines.iter().any(|line| run_length(line) > 5) {
+        return true;
+    }
+    let fours: usize = lines.iter().map(count_fours).sum();
+    let threes = lines
+        .iter()
+        .filter(|line| count_fours(line) == 0 && is_three(line))
+        .count();
+    fours >= 2 || threes >= 2
+}
+
+// 当前棋盘上黑方的全部禁手点
+pub fn forbidden_points(board: &[Vec<ProbPiece>]) -> Vec<(usize, usize)> {
+    let size = board.len();
+    (0..size)
+        .flat_map(|row| (0..size).map(move |col| (row, col)))
+        .filter(|&(row, col)| board[row][col] == ProbPiece::Empty && is_forbidden(board, row, col))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: usize = 15;
+
+    // 黑棋用90%黑棋子，白棋用90%白棋子
+    fn board_with(black: &[(usize, usize)], white: &[(usize, usize)]) -> Vec<Vec<ProbPiece>> {
+        let mut board = vec![vec![ProbPiece::Empty; SIZE]; SIZE];
+        for &(row, col) in black {
+            board[row][col] = ProbPiece::BLACK_90;
+        }
+        for &(row, col) in white {
+            board[row][col] = ProbPiece::BLACK_10;
+        }
+        board
+    }
+
+    #[test]
+    fn double_three() {
+        let board = board_with(&[(7, 5), (7, 6), (5, 7), (6, 7)], &[]);
+        assert!(is_forbidden(&board, 7, 7));
+        // 一条线被白棋挡住后只剩一个三
+        let blocked = board_with(&[(7, 5), (7, 6), (5, 7), (6, 7)], &[(4, 7), (8, 7)]);
+        assert!(!is_forbidden(&blocked, 7, 7));
+    }
+
+    #[test]
+    fn double_four_on_one_line() {
+        // B_B●B_B：两个成五点互不相干
+        let board = board_with(&[(7, 3), (7, 5), (7, 7), (7, 9)], &[]);
+        assert!(is_forbidden(&board, 7, 6));
+        // 活四只算一个四
+        let open_four = board_with(&[(7, 4), (7, 5), (7, 6)], &[]);
+        assert!(!is_forbidden(&open_four, 7, 7));
+    }
+
+    #[test]
+    fn double_four_across_lines() {
+        let board = board_with(&[(7, 4), (7, 5), (7, 6), (4, 7), (5, 7), (6, 7)], &[(7, 3), (3, 7)]);
+        assert!(is_forbidden(&board, 7, 7));
+        // 90%白棋挡住竖线的成五点后只剩一个四
+        let blocked = board_with(&[(7, 4), (7, 5), (7, 6), (4, 7), (5, 7), (6, 7)], &[(7, 3), (3, 7), (8, 7)]);
+        assert!(!is_forbidden(&blocked, 7, 7));
+    }
+
+    #[test]
+    fn overline() {
+        let board = board_with(&[(7, 2), (7, 3), (7, 4), (7, 5), (7, 7)], &[]);
+        assert!(is_forbidden(&board, 7, 6));
+        assert!(forbidden_points(&board).contains(&(7, 6)));
+    }
+
+    #[test]
+    fn five_beats_forbidden() {
+        // 横线成五的同时竖线成四、斜线成三：成五优先
+        let board = board_with(&[(7, 3), (7, 4), (7, 5), (7, 6), (4, 7), (5, 7), (6, 7), (5, 5), (6, 6)], &[(3, 7)]);
+        assert!(!is_forbidden(&board, 7, 7));
+        assert!(!forbidden_points(&board).contains(&(7, 7)));
+    }
+}
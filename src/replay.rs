@@ -0,0 +1,117 @@
+use macroquad::prelude::*;
+
+use crate::i18n::{fill, Strings};
+use crate::layout::Layout;
+use crate::record::GameRecord;
+use crate::text::{draw_ui_text, measure_ui_text};
+use crate::theme::Theme;
+use crate::ui::{Button, ButtonStyle, UiAction};
+use crate::{GameState, Player};
+
+// 复盘状态：对局记录、当前查看到的步数与该步的局面
+pub struct Replay {
+    pub record: GameRecord,
+    pub step: usize, // 已显示的落子数
+    state: GameState,
+}
+
+impl Replay {
+    pub fn new(record: GameRecord) -> Self {
+        let state = replay_to(&record, 0);
+        Replay { record, step: 0, state }
+    }
+
+    // 当前步数对应的局面（步数变化时才重新计算）
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    pub fn step_forward(&mut self) {
+        self.go_to((self.step + 1).min(self.record.moves.len()));
+    }
+
+    pub fn step_back(&mut self) {
+        self.go_to(self.step.saturating_sub(1));
+    }
+
+    fn go_to(&mut self, step: usize) {
+        if step != self.step {
+            self.step = step;
+            self.state = replay_to(&self.record, step);
+        }
+    }
+}
+
+// 按记录重放前 step 手得到的局面（含回合结束时的概率漂移与关联组）
+fn replay_to(record: &GameRecord, step: usize) -> GameState {
+    let record = GameRecord {
+        moves: record.moves[..step].to_vec(),
+        ..record.clone()
+    };
+    GameState::from_record(&record)
+}
+
+// 复盘按钮：上一步、下一步、返回菜单
+pub fn replay_buttons(layout: &Layout, strings: &Strings, replay: &Replay) -> Vec<Button> {
+    let scale = layout.scale;
+    let style = ButtonStyle {
+        color: Color::new(0.2, 0.4, 0.8, 0.9),
+        hover_color: Color::new(0.1, 0.3, 0.7, 0.9),
+        disabled_color: Color::new(0.5, 0.5, 0.5, 0.7),
+        text_color: WHITE,
+        text_size: 22.0 * scale,
+        border_width: 0.0,
+    };
+    let button_y = layout.ui_y(160.0);
+    let button_width = 160.0 * scale;
+    let button_height = 50.0 * scale;
+    let spacing = 30.0 * scale;
+    let left = layout.center_x() - (button_width * 3.0 + spacing * 2.0) / 2.0;
+    let rect = |index: usize| Rect::new(left + index as f32 * (button_width + spacing), button_y, button_width, button_height);
+
+    vec![
+        Button::new(UiAction::ReplayPrev, rect(0), strings.replay_prev, style)
+            .enabled(replay.step > 0)
+            .shortcut(KeyCode::Left),
+        Button::new(UiAction::ReplayNext, rect(1), strings.replay_next, style)
+            .enabled(replay.step < replay.record.moves.len())
+            .shortcut(KeyCode::Right),
+        Button::new(UiAction::Back, rect(2), strings.menu, style).shortcut(KeyCode::Escape),
+    ]
+}
+
+// 绘制复盘信息：步数、最后一步标记和对局结果
+pub fn draw_replay_info(layout: &Layout, theme: &Theme, strings: &Strings, replay: &Replay) {
+    let scale = layout.scale;
+    let total = replay.record.moves.len();
+
+    let title = fill(strings.replay_move, &[&replay.step, &total]);
+    let title_size = 28.0 * scale;
+    let title_width = measure_ui_text(&title, title_size).width;
+    draw_ui_text(&title, layout.center_x() - title_width / 2.0, layout.ui_y(50.0), title_size, theme.text);
+
+    if replay.step > 0 {
+        let mv = replay.record.moves[replay.step - 1];
+        let (x, y) = layout.intersection(mv.row, mv.col);
+        let marker_color = match mv.player {
+            Player::Black => theme.last_move_black,
+            Player::White => theme.last_move_white,
+        };
+        draw_circle_lines(x, y, layout.piece_radius + 2.0 * scale, 3.0 * scale, marker_color);
+    }
+
+    if replay.step == total {
+        if let Some(outcome) = replay.record.result {
+            let result = outcome.text(strings);
+            let result_size = 26.0 * scale;
+            let result_width = measure_ui_text(result, result_size).width;
+            draw_ui_text(
+                result,
+                layout.center_x() - result_width / 2.0,
+                layout.ui_y(100.0),
+                result_size,
+                theme.rules_title,
+            );
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::i18n::Language;
+
+// 设置文件：用户配置目录下的 probability_gomoku/settings.txt
+const SETTINGS_FILE: &str = "settings.txt";
+
+// 动画速度
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnimationSpeed {
+    Off,
+    Slow,
+    Normal,
+    Fast,
+}
+
+// 用户偏好设置：启动时读取，修改后立即保存
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+    pub theme: String,                   // 主题名称
+    pub language: Language,              // 界面语言
+    pub sound: bool,                     // 是否开启音效（关闭即静音）
+    pub volume: u8,                      // 音量（0-100）
+    pub show_prob_text: bool,            // 是否显示概率文字（棋子百分比与下一手提示）
+    pub split_stones: bool,              // 棋子按黑白概率分色绘制（扇形）
+    pub show_coordinates: bool,          // 是否显示棋盘坐标
+    pub confirm_end_turn: bool,          // 结束回合前是否需要再次确认
+    pub animation_speed: AnimationSpeed, // 动画速度
+    pub font_path: Option<String>,       // 自定义界面字体文件（用于显示中文）
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            theme: "Classic".to_owned(),
+            language: Language::from_system(),
+            sound: true,
+            volume: 80,
+            show_prob_text: true,
+            split_stones: false,
+            show_coordinates: false,
+            confirm_end_turn: false,
+            animation_speed: AnimationSpeed::Normal,
+            font_path: None,
+        }
+    }
+}
+
+impl AnimationSpeed {
+    // 动画播放速度倍率，关闭时为None
+    pub fn factor(self) -> Option<f32> {
+        match self {
+            AnimationSpeed::Off => None,
+            AnimationSpeed::Slow => Some(0.5),
+            AnimationSpeed::Normal => Some(1.0),
+            AnimationSpeed::Fast => Some(2.0),
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            AnimationSpeed::Off => "off",
+            AnimationSpeed::Slow => "slow",
+            AnimationSpeed::Normal => "normal",
+            AnimationSpeed::Fast => "fast",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<AnimationSpeed> {
+        match key {
+            "off" => Some(AnimationSpeed::Off),
+            "slow" => Some(AnimationSpeed::Slow),
+            "normal" => Some(AnimationSpeed::Normal),
+            "fast" => Some(AnimationSpeed::Fast),
+            _ => None,
+        }
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "on" => Some(true),
+        "off" => Some(false),
+        _ => None,
+    }
+}
+
+fn bool_key(value: bool) -> &'static str {
+    if value { "on" } else { "off" }
+}
+
+impl Settings {
+    // 序列化为逐行的 "键 值" 文本
+    pub fn to_text(&self) -> String {
+        let mut lines = vec![
+            format!("theme {}", self.theme),
+            format!("language {}", self.language.key()),
+            format!("sound {}", bool_key(self.sound)),
+            format!("volume {}", self.volume),
+            format!("show_prob_text {}", bool_key(self.show_prob_text)),
+            format!("split_stones {}", bool_key(self.split_stones)),
+            format!("show_coordinates {}", bool_key(self.show_coordinates)),
+            format!("confirm_end_turn {}", bool_key(self.confirm_end_turn)),
+            format!("animation_speed {}", self.animation_speed.key()),
+        ];
+        if let Some(font_path) = &self.font_path {
+            lines.push(format!("font {}", font_path));
+        }
+        lines.join("\n") + "\n"
+    }
+
+    // 从文本解析设置：无法识别的行保留默认值
+    pub fn from_text(text: &str) -> Settings {
+        let mut settings = Settings::default();
+        for line in text.lines() {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let value = value.trim();
+            match key {
+                "theme" if !value.is_empty() => settings.theme = value.to_owned(),
+                "language" => settings.language = Language::from_key(value).unwrap_or(settings.language),
+                "sound" => settings.sound = parse_bool(value).unwrap_or(settings.sound),
+                "volume" => {
+                    settings.volume = value.parse().ok().filter(|volume| *volume <= 100).unwrap_or(settings.volume)
+                }
+                "show_prob_text" => settings.show_prob_text = parse_bool(value).unwrap_or(settings.show_prob_text),
+                "split_stones" => settings.split_stones = parse_bool(value).unwrap_or(settings.split_stones),
+                "show_coordinates" => settings.show_coordinates = parse_bool(value).unwrap_or(settings.show_coordinates),
+                "confirm_end_turn" => settings.confirm_end_turn = parse_bool(value).unwrap_or(settings.confirm_end_turn),
+                "animation_speed" => {
+                    settings.animation_speed = AnimationSpeed::from_key(value).unwrap_or(settings.animation_speed)
+                }
+                "font" if !value.is_empty() => settings.font_path = Some(value.to_owned()),
+                _ => {}
+            }
+        }
+        settings
+    }
+
+    // 读取设置文件，不存在或无法读取时使用默认设置
+    pub fn load() -> Settings {
+        settings_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|text| Settings::from_text(&text))
+            .unwrap_or_default()
+    }
+
+    // 保存设置文件
+    pub fn save(&self) -> io::Result<()> {
+        let dir = config_dir().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join(SETTINGS_FILE), self.to_text())
+    }
+}
+
+// 用户配置目录下的游戏目录（设置与主题文件）
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("probability_gomoku"))
+}
+
+fn settings_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(SETTINGS_FILE))
+}
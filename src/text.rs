@@ -0,0 +1,122 @@
+use std::cell::RefCell;
+use std::fs;
+use std::path::Path;
+
+use macroquad::prelude::*;
+
+use crate::i18n::Language;
+
+// 常见系统中支持中日韩文字的字体位置
+const CJK_FONT_PATHS: [&str; 12] = [
+    "C:\\Windows\\Fonts\\msyh.ttc",
+    "C:\\Windows\\Fonts\\simhei.ttf",
+    "/System/Library/Fonts/PingFang.ttc",
+    "/System/Library/Fonts/STHeiti Medium.ttc",
+    "/Library/Fonts/Arial Unicode.ttf",
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/opentype/noto/NotoSansCJKsc-Regular.otf",
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+    "/usr/share/fonts/wenquanyi/wqy-microhei/wqy-microhei.ttc",
+    "/usr/share/fonts/truetype/droid/DroidSansFallbackFull.ttf",
+];
+
+thread_local! {
+    // 界面文字使用的字体，为None时使用macroquad内置字体
+    static UI_FONT: RefCell<Option<Font>> = const { RefCell::new(None) };
+}
+
+// 读取字体文件
+fn load_font_file(path: &Path) -> Option<Font> {
+    let bytes = fs::read(path).ok()?;
+    load_ttf_font_from_bytes(&bytes).ok()
+}
+
+// 加载支持中文的字体：优先使用设置中指定的字体，其次查找系统字体
+fn load_cjk_font(custom_path: Option<&str>) -> Option<Font> {
+    custom_path
+        .into_iter()
+        .chain(CJK_FONT_PATHS)
+        .find_map(|path| load_font_file(Path::new(path)))
+}
+
+// 设置界面文字字体
+fn set_ui_font(font: Option<Font>) {
+    UI_FONT.with(|ui_font| *ui_font.borrow_mut() = font);
+}
+
+// 是否已加载界面字体
+fn has_ui_font() -> bool {
+    UI_FONT.with(|ui_font| ui_font.borrow().is_some())
+}
+
+// 按界面语言切换字体：中文需要加载支持中文的字体，英文默认使用内置字体
+pub fn update_ui_font(language: Language, custom_path: Option<&str>) {
+    if !language.needs_cjk_font() && custom_path.is_none() {
+        set_ui_font(None);
+        return;
+    }
+    if has_ui_font() {
+        return;
+    }
+    match load_cjk_font(custom_path) {
+        Some(font) => set_ui_font(Some(font)),
+        None => eprintln!("Failed to load a font with Chinese glyphs: set \"font <path>\" in settings.txt"),
+    }
+}
+
+// 使用界面字体绘制文字
+pub fn draw_ui_text(text: &str, x: f32, y: f32, font_size: f32, color: Color) {
+    UI_FONT.with(|ui_font| {
+        draw_text_ex(
+            text,
+            x,
+            y,
+            TextParams {
+                font: ui_font.borrow().as_ref(),
+                font_size: font_size as u16,
+                color,
+                ..Default::default()
+            },
+        );
+    });
+}
+
+// 使用界面字体测量文字尺寸
+pub fn measure_ui_text(text: &str, font_size: f32) -> TextDimensions {
+    UI_FONT.with(|ui_font| measure_text(text, ui_font.borrow().as_ref(), font_size as u16, 1.0))
+}
+
+// 按最大宽度折行：英文在空格处断开，中文等非ASCII文字可在任意两字之间断开
+pub fn wrap_ui_text(text: &str, font_size: f32, max_width: f32) -> Vec<String> {
+    // 拆成不可再分的片段：连续的ASCII字符为一个单词，其余每个字符单独成段；记下片段前是否有空格
+    let mut tokens: Vec<(String, bool)> = Vec::new();
+    let mut spaced = false;
+    for ch in text.chars() {
+        if ch == ' ' {
+            spaced = true;
+            continue;
+        }
+        match tokens.last_mut() {
+            Some((token, _)) if ch.is_ascii() && !spaced && token.ends_with(|last: char| last.is_ascii()) => token.push(ch),
+            _ => tokens.push((ch.to_string(), spaced)),
+        }
+        spaced = false;
+    }
+
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for (token, spaced) in tokens {
+        let candidate = if spaced && !line.is_empty() { format!("{} {}", line, token) } else { format!("{}{}", line, token) };
+        if !line.is_empty() && measure_ui_text(&candidate, font_size).width > max_width {
+            lines.push(std::mem::replace(&mut line, token));
+        } else {
+            line = candidate;
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
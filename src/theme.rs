@@ -0,0 +1,565 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use macroquad::prelude::*;
+
+use crate::settings::config_dir;
+use crate::ProbPiece;
+
+// 主题文件目录：用户配置目录下的 probability_gomoku/themes/*.theme
+//
+// 主题文件为逐行的 "键 值" 文本，# 开头为注释。name 为必填项，base 指定继承的内置主题
+// （默认 Classic），其余未写出的项沿用 base 主题。颜色写作 #rrggbb 或 #rrggbbaa，
+// 键名与 Theme 字段同名，四种概率棋子分别为 prob_stone_90/70/30/10。例如：
+//
+//     name Forest
+//     base Wood
+//     board #8fb36a
+//     board_texture wood
+//     board_image forest.png
+//     stone_style glossy
+//     tier_marks on
+//     prob_stone_90 #1b3a1b
+const THEMES_DIR: &str = "themes";
+const THEME_EXTENSION: &str = "theme";
+
+// 棋盘纹理
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BoardTexture {
+    Plain,          // 纯色（使用 board 颜色）
+    Wood,           // 程序生成的木纹
+    Image(PathBuf), // 主题文件指定的图片
+}
+
+// 棋子绘制方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StoneStyle {
+    Flat,   // 纯色圆
+    Glossy, // 带高光的立体棋子
+}
+
+// 主题：棋盘、棋子与界面面板的配色
+#[derive(Clone, Debug)]
+pub struct Theme {
+    pub name: String,
+    pub background: Color,          // 窗口背景
+    pub board: Color,               // 棋盘底色（木纹主题为纹理的基色）
+    pub board_texture: BoardTexture,// 棋盘纹理
+    pub grid: Color,                // 棋盘网格线
+    pub star: Color,                // 星位点
+    pub coordinates: Color,         // 坐标文字
+    pub stone_style: StoneStyle,    // 棋子绘制方式
+    pub stone_outline: Color,       // 棋子描边（透明则不描边）
+    pub tier_marks: bool,           // 70%/30% 棋子加内圈标记，不单靠颜色区分档位
+    pub prob_stones: [Color; 4],    // 90%/70%/30%/10% 黑棋概率的棋子颜色
+    pub prob_text: Color,           // 棋子上的概率文字
+    pub black_stone: Color,         // 预览棋盘中的黑棋
+    pub white_stone: Color,         // 预览棋盘中的白棋
+    pub preview_stone: Color,       // 落子预览的半透明棋子
+    pub cursor: Color,              // 键盘光标方框
+    pub forbidden: Color,           // 连珠禁手点的叉号
+    pub entangle: Color,            // 纠缠棋子对之间的连线
+    pub observation_overlay: Color, // 预览棋盘的遮罩
+    pub win_highlight: Color,       // 获胜棋子边框
+    pub last_move_black: Color,     // 复盘中黑方最后一手的标记
+    pub last_move_white: Color,     // 复盘中白方最后一手的标记
+    pub banner: Color,              // 获胜横幅背景
+    pub banner_text: Color,         // 获胜横幅文字
+    pub panel: Color,               // 信息面板背景
+    pub text: Color,                // 普通文字
+    pub hint_ready: Color,          // 可以结束回合的提示
+    pub hint_waiting: Color,        // 等待落子的提示
+    pub rules_panel: Color,         // 规则面板背景
+    pub rules_title: Color,         // 规则标题
+}
+
+impl Theme {
+    // 经典主题：白色背景与灰度棋子
+    pub fn classic() -> Theme {
+        Theme {
+            name: "Classic".to_owned(),
+            background: WHITE,
+            board: WHITE,
+            board_texture: BoardTexture::Plain,
+            grid: DARKGRAY,
+            star: BLACK,
+            coordinates: DARKGRAY,
+            stone_style: StoneStyle::Flat,
+            stone_outline: Color::new(0.0, 0.0, 0.0, 0.0),
+            tier_marks: false,
+            prob_stones: [
+                Color::new(0.1, 0.1, 0.1, 1.0),
+                Color::new(0.3, 0.3, 0.3, 1.0),
+                Color::new(0.6, 0.6, 0.6, 1.0),
+                Color::new(0.8, 0.8, 0.8, 1.0),
+            ],
+            prob_text: WHITE,
+            black_stone: BLACK,
+            white_stone: WHITE,
+            preview_stone: Color::new(0.2, 0.2, 0.2, 0.4),
+            cursor: Color::new(0.1, 0.4, 0.9, 0.9),
+            forbidden: Color::new(0.85, 0.1, 0.1, 0.9),
+            entangle: Color::new(0.55, 0.2, 0.75, 0.9),
+            observation_overlay: Color::new(0.0, 0.0, 0.0, 0.3),
+            win_highlight: Color::new(1.0, 0.0, 0.0, 1.0),
+            last_move_black: Color::new(0.9, 0.2, 0.2, 1.0),
+            last_move_white: Color::new(0.2, 0.5, 0.9, 1.0),
+            banner: Color::new(1.0, 1.0, 0.0, 0.95),
+            banner_text: BLACK,
+            panel: Color::new(0.9, 0.9, 0.9, 0.8),
+            text: BLACK,
+            hint_ready: Color::new(0.0, 0.7, 0.0, 1.0),
+            hint_waiting: Color::new(0.8, 0.0, 0.0, 1.0),
+            rules_panel: Color::new(0.95, 0.95, 0.95, 0.9),
+            rules_title: Color::new(0.8, 0.2, 0.2, 1.0),
+        }
+    }
+
+    // 夜间主题：深色背景，适合暗光环境
+    pub fn dark() -> Theme {
+        Theme {
+            name: "Dark".to_owned(),
+            background: Color::new(0.16, 0.15, 0.14, 1.0),
+            board: Color::new(0.16, 0.15, 0.14, 1.0),
+            grid: Color::new(0.55, 0.52, 0.48, 1.0),
+            star: Color::new(0.75, 0.72, 0.68, 1.0),
+            coordinates: Color::new(0.7, 0.7, 0.7, 1.0),
+            prob_stones: [
+                Color::new(0.02, 0.02, 0.02, 1.0),
+                Color::new(0.3, 0.3, 0.3, 1.0),
+                Color::new(0.65, 0.65, 0.65, 1.0),
+                Color::new(0.92, 0.92, 0.92, 1.0),
+            ],
+            prob_text: Color::new(0.95, 0.3, 0.3, 1.0),
+            black_stone: Color::new(0.02, 0.02, 0.02, 1.0),
+            white_stone: Color::new(0.95, 0.95, 0.95, 1.0),
+            preview_stone: Color::new(0.8, 0.8, 0.8, 0.35),
+            cursor: Color::new(0.3, 0.7, 1.0, 0.9),
+            forbidden: Color::new(1.0, 0.4, 0.35, 0.9),
+            entangle: Color::new(0.75, 0.55, 1.0, 0.9),
+            observation_overlay: Color::new(0.0, 0.0, 0.0, 0.45),
+            win_highlight: Color::new(1.0, 0.3, 0.2, 1.0),
+            banner: Color::new(0.95, 0.75, 0.1, 0.95),
+            banner_text: BLACK,
+            panel: Color::new(0.28, 0.27, 0.26, 0.9),
+            text: Color::new(0.92, 0.92, 0.92, 1.0),
+            hint_ready: Color::new(0.3, 0.9, 0.3, 1.0),
+            hint_waiting: Color::new(1.0, 0.45, 0.4, 1.0),
+            rules_panel: Color::new(0.22, 0.21, 0.2, 0.9),
+            rules_title: Color::new(1.0, 0.5, 0.4, 1.0),
+            ..Theme::classic()
+        }
+    }
+
+    // 木纹主题：木制棋盘与带高光的棋子
+    pub fn wood() -> Theme {
+        Theme {
+            name: "Wood".to_owned(),
+            background: Color::new(0.93, 0.89, 0.81, 1.0),
+            board: Color::new(0.86, 0.69, 0.43, 1.0),
+            board_texture: BoardTexture::Wood,
+            grid: Color::new(0.25, 0.16, 0.08, 1.0),
+            star: Color::new(0.25, 0.16, 0.08, 1.0),
+            coordinates: Color::new(0.35, 0.24, 0.12, 1.0),
+            stone_style: StoneStyle::Glossy,
+            stone_outline: Color::new(0.0, 0.0, 0.0, 0.35),
+            prob_stones: [
+                Color::new(0.05, 0.05, 0.05, 1.0),
+                Color::new(0.33, 0.3, 0.28, 1.0),
+                Color::new(0.72, 0.7, 0.66, 1.0),
+                Color::new(0.97, 0.96, 0.93, 1.0),
+            ],
+            prob_text: Color::new(0.9, 0.15, 0.1, 1.0),
+            white_stone: Color::new(0.97, 0.96, 0.93, 1.0),
+            preview_stone: Color::new(0.1, 0.1, 0.1, 0.35),
+            panel: Color::new(0.98, 0.95, 0.88, 0.85),
+            rules_panel: Color::new(0.98, 0.95, 0.88, 0.9),
+            ..Theme::classic()
+        }
+    }
+
+    // 高对比度主题：黑底白线，棋子带描边与档位标记
+    pub fn high_contrast() -> Theme {
+        Theme {
+            name: "High Contrast".to_owned(),
+            background: BLACK,
+            board: BLACK,
+            grid: WHITE,
+            star: WHITE,
+            coordinates: WHITE,
+            stone_outline: WHITE,
+            tier_marks: true,
+            prob_stones: [
+                BLACK,
+                Color::new(0.35, 0.35, 0.35, 1.0),
+                Color::new(0.75, 0.75, 0.75, 1.0),
+                WHITE,
+            ],
+            prob_text: Color::new(1.0, 0.0, 1.0, 1.0),
+            black_stone: BLACK,
+            white_stone: WHITE,
+            preview_stone: Color::new(1.0, 1.0, 0.0, 0.5),
+            cursor: Color::new(0.0, 1.0, 1.0, 1.0),
+            forbidden: Color::new(1.0, 0.0, 1.0, 1.0),
+            entangle: Color::new(0.0, 1.0, 1.0, 1.0),
+            observation_overlay: Color::new(0.0, 0.0, 0.0, 0.6),
+            win_highlight: Color::new(0.0, 1.0, 0.0, 1.0),
+            last_move_black: Color::new(1.0, 1.0, 0.0, 1.0),
+            last_move_white: Color::new(0.0, 1.0, 1.0, 1.0),
+            banner: Color::new(1.0, 1.0, 0.0, 1.0),
+            banner_text: BLACK,
+            panel: Color::new(0.15, 0.15, 0.15, 1.0),
+            text: WHITE,
+            hint_ready: Color::new(0.0, 1.0, 0.0, 1.0),
+            hint_waiting: Color::new(1.0, 1.0, 0.0, 1.0),
+            rules_panel: Color::new(0.1, 0.1, 0.1, 1.0),
+            rules_title: Color::new(1.0, 1.0, 0.0, 1.0),
+            ..Theme::classic()
+        }
+    }
+
+    // 色盲友好主题：Okabe-Ito 配色，黑方偏蓝、白方偏橙，档位另有内圈标记
+    pub fn colour_blind() -> Theme {
+        Theme {
+            name: "Colour-blind Safe".to_owned(),
+            background: Color::new(0.97, 0.97, 0.95, 1.0),
+            board: Color::new(0.97, 0.97, 0.95, 1.0),
+            stone_outline: Color::new(0.2, 0.2, 0.2, 0.8),
+            tier_marks: true,
+            prob_stones: [
+                Color::new(0.0, 0.27, 0.53, 1.0),
+                Color::new(0.34, 0.71, 0.91, 1.0),
+                Color::new(0.9, 0.62, 0.0, 1.0),
+                Color::new(0.94, 0.89, 0.26, 1.0),
+            ],
+            prob_text: BLACK,
+            black_stone: Color::new(0.0, 0.27, 0.53, 1.0),
+            white_stone: Color::new(0.94, 0.89, 0.26, 1.0),
+            preview_stone: Color::new(0.0, 0.0, 0.0, 0.3),
+            cursor: Color::new(0.0, 0.45, 0.7, 1.0),
+            forbidden: Color::new(0.84, 0.37, 0.0, 1.0),
+            entangle: Color::new(0.0, 0.45, 0.7, 1.0),
+            win_highlight: Color::new(0.8, 0.47, 0.65, 1.0),
+            last_move_black: Color::new(0.8, 0.47, 0.65, 1.0),
+            last_move_white: Color::new(0.0, 0.62, 0.45, 1.0),
+            banner: Color::new(0.94, 0.89, 0.26, 0.95),
+            hint_ready: Color::new(0.0, 0.45, 0.7, 1.0),
+            hint_waiting: Color::new(0.84, 0.37, 0.0, 1.0),
+            rules_title: Color::new(0.84, 0.37, 0.0, 1.0),
+            ..Theme::classic()
+        }
+    }
+
+    // 概率棋子的颜色：在 90%/70%/30%/10% 四档颜色之间按百分比线性插值，叠加棋子为半透明
+    pub fn prob_stone(&self, piece: ProbPiece) -> Color {
+        const TIERS: [f32; 4] = [90.0, 70.0, 30.0, 10.0];
+        let (percent, alpha) = match piece {
+            ProbPiece::Stone(percent) => (percent, 1.0),
+            ProbPiece::Superposed(percent) => (percent, 0.5),
+            ProbPiece::Empty => return self.background,
+        };
+        let percent = (percent as f32).clamp(TIERS[3], TIERS[0]);
+        let upper = TIERS.iter().rposition(|&tier| tier >= percent).unwrap_or(0).min(2);
+        let t = (TIERS[upper] - percent) / (TIERS[upper] - TIERS[upper + 1]);
+        let (from, to) = (self.prob_stones[upper], self.prob_stones[upper + 1]);
+        Color::new(
+            from.r + (to.r - from.r) * t,
+            from.g + (to.g - from.g) * t,
+            from.b + (to.b - from.b) * t,
+            (from.a + (to.a - from.a) * t) * alpha,
+        )
+    }
+
+    // 按主题绘制一枚棋子：纯色或带高光，描边可选
+    pub fn draw_stone(&self, x: f32, y: f32, radius: f32, color: Color) {
+        draw_circle(x, y, radius, color);
+        if self.stone_style == StoneStyle::Glossy {
+            let shade = Color::new(0.0, 0.0, 0.0, 0.18);
+            draw_circle(x + radius * 0.12, y + radius * 0.12, radius * 0.82, shade);
+            draw_circle(x - radius * 0.05, y - radius * 0.05, radius * 0.8, color);
+            let highlight = Color::new(1.0, 1.0, 1.0, 0.35 * color.a);
+            draw_circle(x - radius * 0.35, y - radius * 0.35, radius * 0.25, highlight);
+        }
+        if self.stone_outline.a > 0.0 {
+            let width = (radius * 0.1).max(1.0);
+            draw_circle_lines(x, y, radius - width / 2.0, width, self.stone_outline);
+        }
+    }
+
+    // 绘制概率棋子：开启档位标记时弱档（黑棋概率在 21%~79% 之间，如 70%/30%）棋子加一圈内环
+    pub fn draw_prob_stone(&self, x: f32, y: f32, radius: f32, piece: ProbPiece) {
+        self.draw_stone(x, y, radius, self.prob_stone(piece));
+        if self.tier_marks && matches!(piece, ProbPiece::Stone(21..=79)) {
+            let width = (radius * 0.12).max(1.0);
+            draw_circle_lines(x, y, radius * 0.55, width, self.prob_text);
+        }
+    }
+
+    // 绘制分色棋子：从正上方顺时针按黑棋概率画出黑色扇形，其余为白色
+    pub fn draw_split_stone(&self, x: f32, y: f32, radius: f32, black_fraction: f32) {
+        const SEGMENTS: usize = 48;
+        draw_circle(x, y, radius, self.white_stone);
+        let filled = (black_fraction.clamp(0.0, 1.0) * SEGMENTS as f32).round() as usize;
+        let point = |index: usize| {
+            let angle = index as f32 / SEGMENTS as f32 * std::f32::consts::TAU;
+            vec2(x + radius * angle.sin(), y - radius * angle.cos())
+        };
+        for index in 0..filled {
+            draw_triangle(vec2(x, y), point(index), point(index + 1), self.black_stone);
+        }
+
+        // 分界线与描边，使白色部分在浅色棋盘上也清晰可见
+        let width = (radius * 0.08).max(1.0);
+        let divider = Color::new(0.5, 0.5, 0.5, 1.0);
+        if filled > 0 && filled < SEGMENTS {
+            let (start, end) = (point(0), point(filled));
+            draw_line(x, y, start.x, start.y, width, divider);
+            draw_line(x, y, end.x, end.y, width, divider);
+        }
+        let outline = if self.stone_outline.a > 0.0 { self.stone_outline } else { divider };
+        draw_circle_lines(x, y, radius - width / 2.0, width, outline);
+    }
+
+    // 绘制棋盘底面：纯色、木纹或图片
+    pub fn draw_board_surface(&self, x: f32, y: f32, size: f32) {
+        match board_texture(&self.board_texture, self.board) {
+            Some(texture) => draw_texture_ex(
+                &texture,
+                x,
+                y,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(vec2(size, size)),
+                    ..Default::default()
+                },
+            ),
+            None => draw_rectangle(x, y, size, size, self.board),
+        }
+    }
+}
+
+// 纹理缓存的键：纹理类型与棋盘基色
+type TextureKey = (BoardTexture, [u8; 4]);
+
+thread_local! {
+    // 已生成或读取的棋盘纹理，避免每帧重复创建
+    static BOARD_TEXTURES: RefCell<HashMap<TextureKey, Option<Texture2D>>> = RefCell::new(HashMap::new());
+}
+
+// 获取棋盘纹理：纯色返回None，图片读取失败时退回纯色
+fn board_texture(texture: &BoardTexture, base: Color) -> Option<Texture2D> {
+    if *texture == BoardTexture::Plain {
+        return None;
+    }
+    let key = (texture.clone(), <[u8; 4]>::from(base));
+    BOARD_TEXTURES.with(|textures| {
+        textures
+            .borrow_mut()
+            .entry(key)
+            .or_insert_with(|| match texture {
+                BoardTexture::Plain => None,
+                BoardTexture::Wood => Some(Texture2D::from_image(&wood_image(base))),
+                BoardTexture::Image(path) => load_board_image(path),
+            })
+            .clone()
+    })
+}
+
+// 程序生成木纹：沿纵向的年轮条纹叠加细微扰动
+fn wood_image(base: Color) -> Image {
+    const SIZE: u16 = 256;
+    let mut image = Image::gen_image_color(SIZE, SIZE, base);
+    for y in 0..SIZE as u32 {
+        for x in 0..SIZE as u32 {
+            let (fx, fy) = (x as f32 / SIZE as f32, y as f32 / SIZE as f32);
+            let warp = (fy * 7.0).sin() * 0.03 + (fy * 23.0 + fx * 3.0).sin() * 0.01;
+            let rings = ((fx + warp) * 38.0).sin() * 0.5 + 0.5;
+            let fibre = ((fx * 211.0).sin() * (fy * 5.0 + fx * 17.0).cos()) * 0.5 + 0.5;
+            let shade = 1.0 - rings.powf(3.0) * 0.12 - fibre * 0.04;
+            image.set_pixel(
+                x,
+                y,
+                Color::new(base.r * shade, base.g * shade, base.b * shade, base.a),
+            );
+        }
+    }
+    image
+}
+
+// 读取主题指定的棋盘图片
+fn load_board_image(path: &Path) -> Option<Texture2D> {
+    let image = fs::read(path)
+        .map_err(|err| err.to_string())
+        .and_then(|bytes| Image::from_file_with_format(&bytes, None).map_err(|err| err.to_string()));
+    match image {
+        Ok(image) => Some(Texture2D::from_image(&image)),
+        Err(err) => {
+            eprintln!("Failed to load board image {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+// 内置主题列表
+pub fn builtin_themes() -> Vec<Theme> {
+    vec![
+        Theme::classic(),
+        Theme::dark(),
+        Theme::wood(),
+        Theme::high_contrast(),
+        Theme::colour_blind(),
+    ]
+}
+
+// 全部可用主题：内置主题加主题目录中的主题文件（同名时文件覆盖内置主题）
+pub fn available_themes() -> Vec<Theme> {
+    let mut themes = builtin_themes();
+    for theme in load_theme_files() {
+        match themes.iter_mut().find(|existing| existing.name == theme.name) {
+            Some(existing) => *existing = theme,
+            None => themes.push(theme),
+        }
+    }
+    themes
+}
+
+// 按名称查找主题，找不到时使用经典主题
+pub fn find_theme(name: &str) -> Theme {
+    available_themes()
+        .into_iter()
+        .find(|theme| theme.name == name)
+        .unwrap_or_else(Theme::classic)
+}
+
+// 读取主题目录中的全部主题文件（按文件名排序）
+fn load_theme_files() -> Vec<Theme> {
+    let Some(dir) = config_dir().map(|dir| dir.join(THEMES_DIR)) else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == THEME_EXTENSION))
+        .collect();
+    paths.sort();
+
+    paths
+        .iter()
+        .filter_map(|path| {
+            let text = fs::read_to_string(path).ok()?;
+            let theme = parse_theme(&text, path.parent().unwrap_or(&dir));
+            if theme.is_none() {
+                eprintln!("Ignoring theme file without a name: {}", path.display());
+            }
+            theme
+        })
+        .collect()
+}
+
+// 解析 #rrggbb 或 #rrggbbaa 颜色
+fn parse_color(value: &str) -> Option<Color> {
+    let hex = value.strip_prefix('#')?;
+    if !hex.is_ascii() || (hex.len() != 6 && hex.len() != 8) {
+        return None;
+    }
+    let channel = |index: usize| u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16).ok();
+    let alpha = if hex.len() == 8 { channel(3)? } else { 255 };
+    Some(Color::from_rgba(channel(0)?, channel(1)?, channel(2)?, alpha))
+}
+
+// 从主题文件文本解析主题：无法识别的行保留 base 主题的取值，缺少 name 时返回None
+fn parse_theme(text: &str, dir: &Path) -> Option<Theme> {
+    let lines: Vec<(&str, &str)> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            (key, value.trim())
+        })
+        .collect();
+
+    let base = lines
+        .iter()
+        .find(|(key, _)| *key == "base")
+        .and_then(|(_, value)| builtin_themes().into_iter().find(|theme| theme.name == *value))
+        .unwrap_or_else(Theme::classic);
+    let mut theme = Theme { name: String::new(), ..base };
+
+    for (key, value) in lines {
+        let color = parse_color(value);
+        let target = match key {
+            "name" => {
+                theme.name = value.to_owned();
+                continue;
+            }
+            "board_texture" => {
+                match value {
+                    "plain" => theme.board_texture = BoardTexture::Plain,
+                    "wood" => theme.board_texture = BoardTexture::Wood,
+                    _ => {}
+                }
+                continue;
+            }
+            "board_image" if !value.is_empty() => {
+                theme.board_texture = BoardTexture::Image(dir.join(value));
+                continue;
+            }
+            "stone_style" => {
+                match value {
+                    "flat" => theme.stone_style = StoneStyle::Flat,
+                    "glossy" => theme.stone_style = StoneStyle::Glossy,
+                    _ => {}
+                }
+                continue;
+            }
+            "tier_marks" => {
+                match value {
+                    "on" => theme.tier_marks = true,
+                    "off" => theme.tier_marks = false,
+                    _ => {}
+                }
+                continue;
+            }
+            "background" => &mut theme.background,
+            "board" => &mut theme.board,
+            "grid" => &mut theme.grid,
+            "star" => &mut theme.star,
+            "coordinates" => &mut theme.coordinates,
+            "stone_outline" => &mut theme.stone_outline,
+            "prob_stone_90" => &mut theme.prob_stones[0],
+            "prob_stone_70" => &mut theme.prob_stones[1],
+            "prob_stone_30" => &mut theme.prob_stones[2],
+            "prob_stone_10" => &mut theme.prob_stones[3],
+            "prob_text" => &mut theme.prob_text,
+            "black_stone" => &mut theme.black_stone,
+            "white_stone" => &mut theme.white_stone,
+            "preview_stone" => &mut theme.preview_stone,
+            "cursor" => &mut theme.cursor,
+            "forbidden" => &mut theme.forbidden,
+            "entangle" => &mut theme.entangle,
+            "observation_overlay" => &mut theme.observation_overlay,
+            "win_highlight" => &mut theme.win_highlight,
+            "last_move_black" => &mut theme.last_move_black,
+            "last_move_white" => &mut theme.last_move_white,
+            "banner" => &mut theme.banner,
+            "banner_text" => &mut theme.banner_text,
+            "panel" => &mut theme.panel,
+            "text" => &mut theme.text,
+            "hint_ready" => &mut theme.hint_ready,
+            "hint_waiting" => &mut theme.hint_waiting,
+            "rules_panel" => &mut theme.rules_panel,
+            "rules_title" => &mut theme.rules_title,
+            _ => continue,
+        };
+        if let Some(color) = color {
+            *target = color;
+        }
+    }
+
+    (!theme.name.is_empty()).then_some(theme)
+}
@@ -0,0 +1,206 @@
+use macroquad::prelude::*;
+
+use crate::i18n::{fill, Strings};
+use crate::layout::Layout;
+use crate::record::MoveRecord;
+use crate::text::{draw_ui_text, measure_ui_text};
+use crate::theme::Theme;
+use crate::{Correlations, Player, ProbPiece, WinCondition};
+
+// 经过一点的四条线：横、竖、两条斜线
+const DIRECTIONS: [(i32, i32); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+// 一个独立随机变量的全部坍缩结果：概率，以及线上各格（下标）的结果（None 为叠加棋子未出现在该点）
+type Outcomes = Vec<(f32, Vec<(usize, Option<Player>)>)>;
+
+// 坍缩后经过该棋子的某条线形成获胜连线的概率（按获胜条件判断长连）
+//
+// 只有与该棋子相连、且距离不超过获胜子数的连续棋子会影响结果，数量有限，
+// 因此把这些棋子按关联组分成相互独立的变量（纠缠对只算一个变量，叠加棋子还要决定出现在哪一点），
+// 枚举全部组合求精确概率。
+pub fn line_win_probability(
+    board: &[Vec<ProbPiece>],
+    correlations: &Correlations,
+    row: usize,
+    col: usize,
+    direction: (i32, i32),
+    win: WinCondition,
+) -> f32 {
+    let size = board.len() as i32;
+    let (dr, dc) = direction;
+    let point_at = |step: i32| {
+        let r = row as i32 + dr * step;
+        let c = col as i32 + dc * step;
+        if r < 0 || r >= size || c < 0 || c >= size || board[r as usize][c as usize] == ProbPiece::Empty {
+            return None;
+        }
+        Some((r as usize, c as usize))
+    };
+
+    let reach = win.length as i32;
+    let back = (1..=reach).take_while(|&step| point_at(-step).is_some()).count() as i32;
+    let forward = (1..=reach).take_while(|&step| point_at(step).is_some()).count() as i32;
+    let cells: Vec<(usize, usize)> = (-back..=forward).filter_map(point_at).collect();
+    let len = cells.len();
+    if len < win.length {
+        return 0.0;
+    }
+
+    let index_of = |point: (usize, usize)| cells.iter().position(|&cell| cell == point);
+    let percent = |(r, c): (usize, usize)| board[r][c].black_percent() as f32 / 100.0;
+    let colours = |black: f32| [(black, Player::Black), (1.0 - black, Player::White)];
+    let mut grouped = vec![false; len];
+    let mut groups: Vec<Outcomes> = Vec::new();
+    for (index, &point) in cells.iter().enumerate() {
+        if grouped[index] {
+            continue;
+        }
+        let superposed = correlations.superpositions.iter().find(|stone| stone.points.contains(&point));
+        let entangled = correlations.entanglements.iter().find(|pair| pair.first == point || pair.second == point);
+        let outcomes: Outcomes = match (superposed, entangled) {
+            (Some(stone), _) => stone
+                .points
+                .iter()
+                .flat_map(|&at| {
+                    colours(percent(at)).map(|(chance, colour)| {
+                        let cells = stone.points.iter().filter_map(|&other| index_of(other).map(|i| (i, other)));
+                        (chance / 2.0, cells.map(|(i, other)| (i, (other == at).then_some(colour))).collect())
+                    })
+                })
+                .collect(),
+            (None, Some(pair)) => colours(percent(pair.first))
+                .map(|(chance, colour)| {
+                    let second = if pair.same { colour } else { colour.opponent() };
+                    let stones = [(pair.first, colour), (pair.second, second)].into_iter();
+                    (chance, stones.filter_map(|(at, colour)| index_of(at).map(|i| (i, Some(colour)))).collect())
+                })
+                .to_vec(),
+            (None, None) => colours(percent(point)).map(|(chance, colour)| (chance, vec![(index, Some(colour))])).to_vec(),
+        };
+        for &(i, _) in &outcomes[0].1 {
+            grouped[i] = true;
+        }
+        groups.push(outcomes);
+    }
+
+    // 经过该棋子（下标back）的获胜窗口起点
+    let center = back as usize;
+    let starts: Vec<usize> = (center.saturating_sub(win.length - 1)..=center)
+        .filter(|start| start + win.length <= len)
+        .collect();
+    let wins = |line: &[Option<Player>]| {
+        let Some(player) = line[center] else {
+            return false;
+        };
+        let same = |index: usize| index < len && line[index] == Some(player);
+        starts.iter().any(|&start| {
+            let end = start + win.length;
+            (start..end).all(same) &&
+                (!win.rule.exact_length(player) || !((start > 0 && same(start - 1)) || same(end)))
+        })
+    };
+
+    // 逐一枚举各变量结果的组合（按进位递增）
+    let mut total = 0.0;
+    let mut picks = vec![0; groups.len()];
+    let mut line = vec![None; len];
+    loop {
+        let mut chance = 1.0;
+        for (outcomes, &pick) in groups.iter().zip(&picks) {
+            let (outcome_chance, cells) = &outcomes[pick];
+            chance *= outcome_chance;
+            for &(index, colour) in cells {
+                line[index] = colour;
+            }
+        }
+        if chance > 0.0 && wins(&line) {
+            total += chance;
+        }
+        let Some(next) = picks.iter().zip(&groups).position(|(&pick, outcomes)| pick + 1 < outcomes.len()) else {
+            break;
+        };
+        picks[next] += 1;
+        picks[..next].fill(0);
+    }
+    total
+}
+
+// 绘制棋子悬浮提示：黑白概率、落子方与手数、四条线的获胜连线概率
+pub fn draw_stone_tooltip(
+    layout: &Layout,
+    theme: &Theme,
+    strings: &Strings,
+    board: &[Vec<ProbPiece>],
+    correlations: &Correlations,
+    moves: &[MoveRecord],
+    win: WinCondition,
+) {
+    let (mouse_x, mouse_y) = mouse_position();
+    let Some((row, col)) = layout.mouse_to_grid(mouse_x, mouse_y) else {
+        return;
+    };
+    let piece = board[row][col];
+    if piece == ProbPiece::Empty {
+        return;
+    }
+
+    let black = piece.black_percent();
+    let mut lines = vec![fill(strings.tooltip_probability, &[&black, &(100 - black)])];
+    if let Some((index, mv)) = moves.iter().enumerate().rev().find(|(_, mv)| (mv.row, mv.col) == (row, col)) {
+        let player = match mv.player {
+            Player::Black => strings.black,
+            Player::White => strings.white,
+        };
+        lines.push(fill(strings.tooltip_placed, &[&player, &(index + 1)]));
+    }
+    if matches!(piece, ProbPiece::Superposed(_)) {
+        lines.push(strings.tooltip_superposed.to_owned());
+    }
+    lines.push(strings.tooltip_lines.to_owned());
+    let names = [
+        strings.line_horizontal,
+        strings.line_vertical,
+        strings.line_diagonal,
+        strings.line_anti_diagonal,
+    ];
+    for (name, direction) in names.into_iter().zip(DIRECTIONS) {
+        let percent = format!("{:.1}", line_win_probability(board, correlations, row, col, direction, win) * 100.0);
+        lines.push(fill(strings.tooltip_line, &[&name, &percent]));
+    }
+
+    let scale = layout.scale;
+    let text_size = 18.0 * scale;
+    let line_height = 22.0 * scale;
+    let padding = 10.0 * scale;
+    let text_width = lines
+        .iter()
+        .map(|line| measure_ui_text(line, text_size).width)
+        .fold(0.0, f32::max);
+    let width = text_width + padding * 2.0;
+    let height = line_height * lines.len() as f32 + padding * 2.0 - (line_height - text_size);
+
+    // 默认显示在鼠标右下方，超出窗口时翻到另一侧
+    let offset = 16.0 * scale;
+    let mut x = mouse_x + offset;
+    let mut y = mouse_y + offset;
+    if x + width > screen_width() {
+        x = mouse_x - offset - width;
+    }
+    if y + height > screen_height() {
+        y = mouse_y - offset - height;
+    }
+    x = x.max(0.0);
+    y = y.max(0.0);
+
+    draw_rectangle(x, y, width, height, Color { a: 0.95, ..theme.panel });
+    draw_rectangle_lines(x, y, width, height, 2.0 * scale, theme.grid);
+    for (index, line) in lines.iter().enumerate() {
+        draw_ui_text(
+            line,
+            x + padding,
+            y + padding + text_size * 0.8 + index as f32 * line_height,
+            text_size,
+            theme.text,
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use macroquad::prelude::*;
+
+use crate::PowerCard;
+use crate::text::{draw_ui_text, measure_ui_text};
+
+const HOVER_SCALE: f32 = 1.05;
+
+// 界面动作：按钮被点击后交给主循环处理
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UiAction {
+    TogglePreview,
+    EndTurn,
+    TogglePause,
+    ToggleEntangle,
+    ToggleSuperpose,
+    SelectCard(PowerCard),
+    Restart,
+    Exit,
+    Menu,
+    NewGame,
+    LoadGame,
+    Replay,
+    Settings,
+    StartGame,
+    Back,
+    CycleOpponent,
+    CycleBoardSize,
+    CycleTiers,
+    CyclePreviews,
+    CyclePreviewRule,
+    CycleTimeControl,
+    CycleWinRule,
+    CycleWinLength,
+    CycleStonesPerTurn,
+    ToggleRenju,
+    CycleOpening,
+    CycleDoubleWin,
+    CycleDrift,
+    CycleEntangle,
+    ToggleSuperposition,
+    CyclePowerCards,
+    ChooseBlack,
+    ChooseWhite,
+    ChooseExtend,
+    ReplayPrev,
+    ReplayNext,
+    CycleTheme,
+    CycleLanguage,
+    ToggleSound,
+    CycleVolume,
+    ToggleProbText,
+    ToggleSplitStones,
+    ToggleCoordinates,
+    ToggleConfirmEndTurn,
+    CycleAnimationSpeed,
+}
+
+// 按钮外观：常态/悬浮/禁用颜色、文字和边框
+#[derive(Clone, Copy, Debug)]
+pub struct ButtonStyle {
+    pub color: Color,
+    pub hover_color: Color,
+    pub disabled_color: Color,
+    pub text_color: Color,
+    pub text_size: f32,
+    pub border_width: f32, // 为0时不绘制边框
+}
+
+// 按钮：同一份矩形同时用于绘制和点击检测
+#[derive(Clone, Debug)]
+pub struct Button {
+    pub action: UiAction,
+    pub rect: Rect,
+    pub label: String,
+    pub enabled: bool,
+    pub shortcut: Option<KeyCode>, // 键盘快捷键
+    pub focused: bool,             // 是否拥有键盘焦点
+    pub style: ButtonStyle,
+}
+
+impl Button {
+    pub fn new(action: UiAction, rect: Rect, label: impl Into<String>, style: ButtonStyle) -> Self {
+        Button {
+            action,
+            rect,
+            label: label.into(),
+            enabled: true,
+            shortcut: None,
+            focused: false,
+            style,
+        }
+    }
+
+    // 设置按钮是否可用
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    // 设置按钮的键盘快捷键
+    pub fn shortcut(mut self, key: KeyCode) -> Self {
+        self.shortcut = Some(key);
+        self
+    }
+
+    // 本帧是否按下了可用按钮的快捷键
+    pub fn shortcut_pressed(&self) -> bool {
+        self.enabled && self.shortcut.is_some_and(is_key_pressed)
+    }
+
+    // 鼠标是否悬浮在可用按钮上
+    pub fn is_hovered(&self) -> bool {
+        let (mouse_x, mouse_y) = mouse_position();
+        self.enabled && self.rect.contains(vec2(mouse_x, mouse_y))
+    }
+
+    // 绘制按钮：悬浮或拥有焦点时以中心为基准轻微放大
+    pub fn draw(&self) {
+        let hovered = self.is_hovered() || (self.enabled && self.focused);
+        let color = if !self.enabled {
+            self.style.disabled_color
+        } else if hovered {
+            self.style.hover_color
+        } else {
+            self.style.color
+        };
+        let scale = if hovered { HOVER_SCALE } else { 1.0 };
+        let width = self.rect.w * scale;
+        let height = self.rect.h * scale;
+        let x = self.rect.x - (width - self.rect.w) / 2.0;
+        let y = self.rect.y - (height - self.rect.h) / 2.0;
+
+        draw_rectangle(x, y, width, height, color);
+        if self.style.border_width > 0.0 {
+            draw_rectangle_lines(x, y, width, height, self.style.border_width, BLACK);
+        }
+        if self.focused {
+            let focus_width = (self.style.border_width).max(3.0);
+            draw_rectangle_lines(x, y, width, height, focus_width, Color::new(0.1, 0.4, 0.9, 1.0));
+        }
+
+        let dims = measure_ui_text(&self.label, self.style.text_size);
+        draw_ui_text(
+            &self.label,
+            x + (width - dims.width) / 2.0,
+            y + (height - dims.height) / 2.0 + dims.offset_y,
+            self.style.text_size,
+            self.style.text_color,
+        );
+    }
+}
+
+// 按动作查找按钮
+pub fn find_button(buttons: &[Button], action: UiAction) -> Option<&Button> {
+    buttons.iter().find(|button| button.action == action)
+}
+
+// 本帧触发的按钮动作：鼠标左键点中按钮或按下按钮快捷键
+pub fn triggered_action(buttons: &[Button]) -> Option<UiAction> {
+    let clicked = is_mouse_button_pressed(MouseButton::Left);
+    buttons
+        .iter()
+        .find(|button| (clicked && button.is_hovered()) || button.shortcut_pressed())
+        .map(|button| button.action)
+}
+
+// 菜单焦点导航：上下方向键/WASD移动焦点（跳过禁用按钮），回车触发焦点按钮
+pub fn navigate_focus(buttons: &mut [Button], focus: &mut usize) -> Option<UiAction> {
+    if buttons.is_empty() {
+        return None;
+    }
+    let count = buttons.len();
+    *focus = (*focus).min(count - 1);
+
+    let step = if is_key_pressed(KeyCode::Down) || is_key_pressed(KeyCode::S) || is_key_pressed(KeyCode::Tab) {
+        1
+    } else if is_key_pressed(KeyCode::Up) || is_key_pressed(KeyCode::W) {
+        count - 1
+    } else {
+        0
+    };
+    if step > 0 || !buttons[*focus].enabled {
+        let step = step.max(1);
+        let mut next = *focus;
+        for _ in 0..count {
+            next = (next + step) % count;
+            if buttons[next].enabled {
+                break;
+            }
+        }
+        *focus = next;
+    }
+
+    for (index, button) in buttons.iter_mut().enumerate() {
+        button.focused = index == *focus;
+    }
+
+    let focused = &buttons[*focus];
+    if focused.enabled && (is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter)) {
+        return Some(focused.action);
+    }
+    triggered_action(buttons)
+}
+
+// 绘制全部按钮
+pub fn draw_buttons(buttons: &[Button]) {
+    for button in buttons {
+        button.draw();
+    }
+}